@@ -1,10 +1,11 @@
 use chrono::NaiveDateTime;
 use ponziland_models::events::auth::AddressAuthorizedEvent;
+use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
 use crate::{events::EventId, utils::date::naive_from_u64};
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct AddressAuthorizedEventModel {
     pub id: Option<EventId>,
     pub at: NaiveDateTime,
@@ -1,9 +1,10 @@
 use ponziland_models::events::auth::VerifierUpdatedEvent;
+use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 
 use crate::events::EventId;
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct VerifierUpdatedEventModel {
     pub id: Option<EventId>,
     pub new_verifier: String,
@@ -5,7 +5,9 @@ use super::{
     auth::{AddressAuthorizedEventModel, AddressRemovedEventModel, VerifierUpdatedEventModel},
     EventId as Id, EventType,
 };
+use crate::shared::Location;
 use ponziland_models::events::EventData;
+use serde::Serialize;
 use sqlx::prelude::FromRow;
 
 #[derive(FromRow, Clone, Debug)]
@@ -15,7 +17,8 @@ pub struct Event {
     pub event_type: EventType,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", content = "data")]
 pub enum DataModel {
     AuctionFinished(AuctionFinishedEventModel),
     LandBought(LandBoughtEventModel),
@@ -38,6 +41,47 @@ impl DataModel {
             DataModel::VerifierUpdated(model) => model.id = Some(id),
         }
     }
+
+    #[must_use]
+    pub fn id(&self) -> Option<&Id> {
+        match self {
+            DataModel::AuctionFinished(model) => model.id.as_ref(),
+            DataModel::LandBought(model) => model.id.as_ref(),
+            DataModel::LandNuked(model) => model.id.as_ref(),
+            DataModel::NewAuction(model) => model.id.as_ref(),
+            DataModel::AddressAuthorized(model) => model.id.as_ref(),
+            DataModel::AddressRemoved(model) => model.id.as_ref(),
+            DataModel::VerifierUpdated(model) => model.id.as_ref(),
+        }
+    }
+
+    /// Location of the land concerned by the event, if any.
+    #[must_use]
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            DataModel::AuctionFinished(model) => Some(model.location),
+            DataModel::LandBought(model) => Some(model.location),
+            DataModel::LandNuked(model) => Some(model.location),
+            DataModel::NewAuction(model) => Some(model.location),
+            DataModel::AddressAuthorized(_)
+            | DataModel::AddressRemoved(_)
+            | DataModel::VerifierUpdated(_) => None,
+        }
+    }
+
+    /// Whether the address is the buyer, seller or owner in the event.
+    #[must_use]
+    pub fn involves(&self, address: &str) -> bool {
+        match self {
+            DataModel::AuctionFinished(model) => model.buyer == address,
+            DataModel::LandBought(model) => model.buyer == address || model.seller == address,
+            DataModel::LandNuked(model) => model.owner == address,
+            DataModel::NewAuction(_)
+            | DataModel::AddressAuthorized(_)
+            | DataModel::AddressRemoved(_)
+            | DataModel::VerifierUpdated(_) => false,
+        }
+    }
 }
 
 impl From<EventData> for DataModel {
@@ -61,7 +105,7 @@ impl From<EventData> for DataModel {
 }
 
 /// An event where the type has been fetched from the database, and is ready to use.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct FetchedEvent {
     pub id: Id,
     pub at: chrono::NaiveDateTime,
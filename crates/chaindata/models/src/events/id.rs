@@ -69,6 +69,19 @@ impl Id {
         })
     }
 
+    /// Formats the id exactly like the torii SQL provider, so it can be used as a torii cursor.
+    #[must_use]
+    pub fn to_torii_string(&self) -> String {
+        // Torii pads the block number to 62 digits, the fixed hex string has 64
+        let block = self.block_id.to_fixed_hex_string();
+        format!(
+            "0x{}:{:#x}:{:#04x}",
+            &block[4..],
+            self.tx_hash,
+            self.event_idx
+        )
+    }
+
     // Testing function, that creates a new block for testing
     // should NEVER be used in production code
     #[must_use]
@@ -216,6 +229,15 @@ mod tests {
                 .unwrap()
         );
         assert_eq!(event_id.event_idx, 0x10);
+
+        assert_eq!(
+            event_id.to_torii_string(),
+            "0x000000000000000000000000000000000000000000000000000000000b63a9:0x5f26258a75882780784979d970a3579c091e92073d61f7e90260e1133f75c8a:0x10"
+        );
+        assert_eq!(
+            Id::parse_from_torii(&event_id.to_torii_string()).unwrap(),
+            event_id
+        );
     }
 
     #[test]
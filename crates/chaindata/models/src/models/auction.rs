@@ -1 +1,56 @@
-// TODO
+use chrono::NaiveDateTime;
+use ponziland_models::models::Auction;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::{
+    events::EventId,
+    shared::{Location, U256},
+    utils::date::{naive_from_u64, u64_from_naive},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Model {
+    pub id: EventId,
+    pub at: NaiveDateTime,
+    pub location: Location,
+    pub start_time: NaiveDateTime,
+    pub start_price: U256,
+    pub floor_price: U256,
+    pub is_finished: bool,
+    pub decay_rate: i32,
+    pub sold_at_price: Option<U256>,
+}
+
+impl Model {
+    #[must_use]
+    pub fn from_at(auction: &Auction, id: EventId, at: NaiveDateTime) -> Self {
+        Self {
+            id,
+            at,
+            location: auction.land_location.into(),
+            start_time: naive_from_u64(auction.start_time),
+            start_price: auction.start_price.into(),
+            floor_price: auction.floor_price.into(),
+            is_finished: auction.is_finished,
+            decay_rate: auction.decay_rate.into(),
+            sold_at_price: auction.sold_at_price.map(Into::into),
+        }
+    }
+}
+
+impl From<&Model> for Auction {
+    // The decay rate is coming from the chain as a u16, so it fits.
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    fn from(model: &Model) -> Self {
+        Self {
+            land_location: *model.location,
+            start_time: u64_from_naive(model.start_time),
+            start_price: *model.start_price,
+            floor_price: *model.floor_price,
+            is_finished: model.is_finished,
+            decay_rate: model.decay_rate as u16,
+            sold_at_price: model.sold_at_price.map(|price| *price),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Data of torii that could not be processed, kept aside so that the synchronization can go on.
+///
+/// `data` is the raw JSON data of torii, and `error` the reason of the failure.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct Model {
+    pub stream: String,
+    pub event_id: String,
+    pub name: String,
+    pub data: String,
+    pub error: String,
+    pub failed_at: NaiveDateTime,
+}
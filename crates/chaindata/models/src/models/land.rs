@@ -1,11 +1,14 @@
+use crate::error::Error;
 use crate::events::EventId;
 use crate::shared::{Location, U256};
-use crate::utils::date::naive_from_u64;
+use crate::utils::date::{naive_from_u64, u64_from_naive};
 use chrono::NaiveDateTime;
 use ponziland_models::models::{Land, Level as RawLevel};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 use sqlx::Type;
+use std::str::FromStr;
+use torii_ingester::prelude::Felt;
 // Unfortunately, we need to re-declare level to have the iterop with the database working with sqlx.
 // Importing the sqlx crate in the external ponziland-models one seems ridiculous, and prevents usage from external users.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Copy, Type)]
@@ -26,6 +29,16 @@ impl From<RawLevel> for Level {
     }
 }
 
+impl From<Level> for RawLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Zero => RawLevel::Zero,
+            Level::First => RawLevel::First,
+            Level::Second => RawLevel::Second,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Model {
     pub id: EventId,
@@ -53,3 +66,19 @@ impl Model {
         }
     }
 }
+
+impl TryFrom<&Model> for Land {
+    type Error = Error;
+
+    fn try_from(model: &Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            location: *model.location,
+            block_date_bought: u64_from_naive(model.bought_at),
+            owner: Felt::from_str(&model.owner).map_err(|_| Error::InvalidPart("owner"))?,
+            sell_price: *model.sell_price,
+            token_used: Felt::from_str(&model.token_used)
+                .map_err(|_| Error::InvalidPart("token_used"))?,
+            level: model.level.into(),
+        })
+    }
+}
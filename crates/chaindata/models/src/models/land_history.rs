@@ -0,0 +1,21 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::Level;
+use crate::shared::U256;
+
+/// State of a land and its stake at a point in time where one of them changed.
+///
+/// The land and stake parts are `None` if they did not exist yet at that time.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Model {
+    pub at: NaiveDateTime,
+    pub owner: Option<String>,
+    pub bought_at: Option<NaiveDateTime>,
+    pub sell_price: Option<U256>,
+    pub token_used: Option<String>,
+    pub level: Option<Level>,
+    pub stake_amount: Option<U256>,
+    pub last_pay_time: Option<NaiveDateTime>,
+}
@@ -6,7 +6,7 @@ use sqlx::FromRow;
 use crate::{
     events::EventId,
     shared::{Location, U256},
-    utils::date::naive_from_u64,
+    utils::date::{naive_from_u64, u64_from_naive},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -30,3 +30,13 @@ impl Model {
         }
     }
 }
+
+impl From<&Model> for LandStake {
+    fn from(model: &Model) -> Self {
+        Self {
+            location: *model.location,
+            last_pay_time: u64_from_naive(model.last_pay_time),
+            amount: *model.amount,
+        }
+    }
+}
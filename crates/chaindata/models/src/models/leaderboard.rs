@@ -0,0 +1,59 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::BigDecimal, FromRow, Type};
+
+use crate::shared::U256;
+
+/// Criteria on which the players are ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "leaderboard_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// Number of lands currently owned.
+    LandsHeld,
+    /// Sell price and stake of the lands currently owned, in the main token.
+    LandValue,
+    /// Number of lands of the player that were nuked.
+    LandsNuked,
+    AuctionsWon,
+    /// Sum of the sell prices minus the buy prices of the lands sold, in the main token at the
+    /// time of each trade.
+    RealizedProfit,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 5] = [
+        Kind::LandsHeld,
+        Kind::LandValue,
+        Kind::LandsNuked,
+        Kind::AuctionsWon,
+        Kind::RealizedProfit,
+    ];
+}
+
+/// A player in a leaderboard snapshot.
+///
+/// All the entries of a snapshot share the same `kind` and `computed_at`.
+#[derive(Debug, Clone, FromRow, PartialEq, Eq)]
+pub struct EntryModel {
+    pub kind: Kind,
+    pub computed_at: NaiveDateTime,
+    /// Position of the player in the leaderboard, starting at 1.
+    pub rank: i32,
+    pub address: String,
+    /// Can be negative for the realized profit.
+    pub score: BigDecimal,
+}
+
+/// Sale of a land by a player, with the price at which the player acquired it.
+#[derive(Debug, Clone, FromRow)]
+pub struct LandSaleModel {
+    pub seller: String,
+    pub sold_at: NaiveDateTime,
+    pub sell_price: U256,
+    pub sell_token: String,
+    pub bought_at: NaiveDateTime,
+    pub buy_price: U256,
+    /// Token used for the purchase, `None` when the land was won at an auction, in the main token.
+    pub buy_token: Option<String>,
+}
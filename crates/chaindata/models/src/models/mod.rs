@@ -1,6 +1,23 @@
 mod auction;
+mod failed_event;
 mod land;
+mod land_history;
 mod land_stake;
+mod leaderboard;
+mod sync_cursor;
+mod token;
+mod token_price;
 
+pub use auction::Model as AuctionModel;
+pub use failed_event::Model as FailedEventModel;
 pub use land::{Level, Model as LandModel};
+pub use land_history::Model as LandHistoryModel;
 pub use land_stake::Model as LandStakeModel;
+pub use leaderboard::{
+    EntryModel as LeaderboardEntryModel, Kind as LeaderboardKind, LandSaleModel,
+};
+pub use sync_cursor::Model as SyncCursorModel;
+pub use token::Model as TokenModel;
+pub use token_price::{
+    CandleModel as PriceCandleModel, Model as TokenPriceModel, SampleModel as PriceSampleModel,
+};
@@ -0,0 +1,14 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Position of a synchronization stream in torii.
+///
+/// `last_event_id` is the raw torii event id (`block:tx_hash:event_idx`) of the last processed
+/// item of the stream.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct Model {
+    pub stream: String,
+    pub last_event_id: String,
+    pub updated_at: NaiveDateTime,
+}
@@ -0,0 +1,16 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Token playable in the game, managed through the admin API.
+///
+/// `address` is a fixed hex string. `name` and `decimals` are unknown when they could not be
+/// read from the contract.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct Model {
+    pub address: String,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub decimals: Option<i16>,
+    pub created_at: NaiveDateTime,
+}
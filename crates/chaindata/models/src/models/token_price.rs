@@ -0,0 +1,41 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::shared::U256;
+
+/// Price of a token against the main token, as read from an ekubo pool.
+///
+/// `ratio` is the amount of token for one main token, as a raw fixed point number with 128
+/// decimal bits. Addresses are fixed hex strings.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct Model {
+    pub token: String,
+    pub at: NaiveDateTime,
+    pub pool_token0: String,
+    pub pool_token1: String,
+    /// Decimal representation of the u128 fee of the pool.
+    pub pool_fee: String,
+    pub pool_tick_spacing: i64,
+    pub pool_extension: String,
+    pub ratio: U256,
+}
+
+/// Open, high, low and close raw ratios of a token during a time bucket.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct CandleModel {
+    /// Start of the bucket.
+    pub at: NaiveDateTime,
+    pub open: U256,
+    pub high: U256,
+    pub low: U256,
+    pub close: U256,
+}
+
+/// Raw ratio of a token saved at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
+pub struct SampleModel {
+    pub token: String,
+    pub at: NaiveDateTime,
+    pub ratio: U256,
+}
@@ -10,3 +10,11 @@ pub fn naive_from_u64(unix_timestamp: u64) -> NaiveDateTime {
         .unwrap()
         .naive_utc()
 }
+
+/// Converts a `NaiveDateTime` back to a Unix timestamp.
+///
+/// Dates before the Unix epoch are clamped to 0.
+#[must_use]
+pub fn u64_from_naive(date: NaiveDateTime) -> u64 {
+    u64::try_from(date.and_utc().timestamp()).unwrap_or(0)
+}
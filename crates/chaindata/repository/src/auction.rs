@@ -0,0 +1,332 @@
+use chaindata_models::{events::EventId, models::AuctionModel, shared::Location};
+use chrono::NaiveDateTime;
+use sqlx::{query, query_as};
+
+use crate::{Database, Error};
+
+pub struct Repository {
+    db: Database,
+}
+
+impl Repository {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Saves an auction model to the database
+    ///
+    /// # Errors
+    /// Returns an error if the auction could not be saved.
+    pub async fn save(&self, auction: AuctionModel) -> Result<EventId, Error> {
+        Ok(query!(
+            r#"
+            INSERT INTO auction (
+                id, at, location, start_time, start_price, floor_price, is_finished, decay_rate,
+                sold_at_price
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+            auction.id as EventId,
+            auction.at,
+            auction.location as Location,
+            auction.start_time,
+            auction.start_price as _,
+            auction.floor_price as _,
+            auction.is_finished,
+            auction.decay_rate,
+            auction.sold_at_price as _
+        )
+        .fetch_one(&mut *(self.db.acquire().await?))
+        .await?
+        .id
+        .parse()?)
+    }
+
+    /// Gets the latest auction model at a specific location at or before the given timestamp
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest_at_location(
+        &self,
+        location: Location,
+        at: NaiveDateTime,
+    ) -> Result<Option<AuctionModel>, sqlx::Error> {
+        query_as!(
+            AuctionModel,
+            r#"
+            SELECT
+                id as "id: _",
+                at,
+                location as "location: Location",
+                start_time,
+                start_price as "start_price: _",
+                floor_price as "floor_price: _",
+                is_finished,
+                decay_rate,
+                sold_at_price as "sold_at_price: _"
+            FROM auction
+            WHERE location = $1 AND at <= $2
+            ORDER BY at DESC
+            LIMIT 1
+            "#,
+            location as Location,
+            at
+        )
+        .fetch_optional(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Gets the latest version of every auction that exists at a specific point in time,
+    /// finished or not.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_all_at_time(
+        &self,
+        at: NaiveDateTime,
+    ) -> Result<Vec<AuctionModel>, sqlx::Error> {
+        // This query gets the most recent version of each auction at or before the specified time
+        query_as!(
+            AuctionModel,
+            r#"
+            WITH latest_auctions AS (
+                SELECT DISTINCT ON (location)
+                    id, at, location, start_time, start_price, floor_price, is_finished,
+                    decay_rate, sold_at_price
+                FROM auction
+                WHERE at <= $1
+                ORDER BY location, at DESC
+            )
+            SELECT
+                id as "id: _",
+                at,
+                location as "location: Location",
+                start_time,
+                start_price as "start_price: _",
+                floor_price as "floor_price: _",
+                is_finished,
+                decay_rate,
+                sold_at_price as "sold_at_price: _"
+            FROM latest_auctions
+            "#,
+            at
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Gets all auctions that are still running at a specific point in time,
+    /// meaning their latest version at that time is not finished.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_active_auctions(
+        &self,
+        at: NaiveDateTime,
+    ) -> Result<Vec<AuctionModel>, sqlx::Error> {
+        query_as!(
+            AuctionModel,
+            r#"
+            WITH latest_auctions AS (
+                SELECT DISTINCT ON (location)
+                    id, at, location, start_time, start_price, floor_price, is_finished,
+                    decay_rate, sold_at_price
+                FROM auction
+                WHERE at <= $1
+                ORDER BY location, at DESC
+            )
+            SELECT
+                id as "id: _",
+                at,
+                location as "location: Location",
+                start_time,
+                start_price as "start_price: _",
+                floor_price as "floor_price: _",
+                is_finished,
+                decay_rate,
+                sold_at_price as "sold_at_price: _"
+            FROM latest_auctions
+            WHERE NOT is_finished
+            ORDER BY location
+            "#,
+            at
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Gets an auction model by ID
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_by_id(&self, id: EventId) -> Result<Option<AuctionModel>, sqlx::Error> {
+        query_as!(
+            AuctionModel,
+            r#"
+            SELECT
+                id as "id: _",
+                at,
+                location as "location: Location",
+                start_time,
+                start_price as "start_price: _",
+                floor_price as "floor_price: _",
+                is_finished,
+                decay_rate,
+                sold_at_price as "sold_at_price: _"
+            FROM auction
+            WHERE id = $1
+            "#,
+            id as EventId
+        )
+        .fetch_optional(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Gets the latest timestamp from the auction table
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest_timestamp(&self) -> Result<Option<NaiveDateTime>, sqlx::Error> {
+        query!(
+            r#"
+            SELECT MAX(at) as latest_time
+            FROM auction
+            "#
+        )
+        .fetch_one(&mut *(self.db.acquire().await?))
+        .await
+        .map(|row| row.latest_time)
+    }
+
+    /// Gets the id of the latest event from the auction table
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest_event_id(&self) -> Result<Option<EventId>, sqlx::Error> {
+        query!(
+            r#"
+            SELECT MAX(id) as "latest_id: EventId"
+            FROM auction
+            "#
+        )
+        .fetch_one(&mut *(self.db.acquire().await?))
+        .await
+        .map(|row| row.latest_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chaindata_models::shared::U256;
+    use chrono::Utc;
+    use migrations::MIGRATOR;
+    use std::str::FromStr;
+
+    fn auction_at(id: EventId, at: NaiveDateTime, location: Location) -> AuctionModel {
+        AuctionModel {
+            id,
+            at,
+            location,
+            start_time: at,
+            start_price: U256::from_str("1000000").unwrap(),
+            floor_price: U256::from_str("1000").unwrap(),
+            is_finished: false,
+            decay_rate: 100,
+            sold_at_price: None,
+        }
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_save_and_get_auction(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+
+        let location: Location = 1234.into();
+        let now = Utc::now().naive_utc();
+        let auction = auction_at(EventId::new_test(0, 0, 0), now, location);
+
+        // Save the auction model
+        let saved_id = repo.save(auction.clone()).await?;
+        assert_eq!(saved_id, auction.id);
+
+        // Retrieve the auction model by ID
+        let retrieved = repo.get_by_id(saved_id).await?;
+        assert!(retrieved.is_some());
+        let retrieved = retrieved.unwrap();
+        assert_eq!(retrieved.id, auction.id);
+        assert_eq!(retrieved.location, auction.location);
+        assert_eq!(retrieved.start_price, auction.start_price);
+        assert_eq!(retrieved.floor_price, auction.floor_price);
+        assert_eq!(retrieved.decay_rate, auction.decay_rate);
+        assert!(retrieved.sold_at_price.is_none());
+
+        // Test get_latest_at_location
+        let latest = repo.get_latest_at_location(location, now).await?;
+        assert_eq!(latest.map(|a| a.id), Some(auction.id.clone()));
+
+        // Test getting auction at a past time
+        let past = now - chrono::Duration::hours(1);
+        let past_latest = repo.get_latest_at_location(location, past).await?;
+        assert!(past_latest.is_none());
+
+        // Test get_all_at_time and get_active_auctions
+        let all_auctions = repo.get_all_at_time(now).await?;
+        assert!(all_auctions.iter().any(|a| a.id == auction.id));
+        let active = repo.get_active_auctions(now).await?;
+        assert!(active.iter().any(|a| a.id == auction.id));
+
+        assert_eq!(repo.get_latest_timestamp().await?, Some(auction.at));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_auction_finishing(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+
+        let location: Location = 5678.into();
+        let other_location: Location = 42.into();
+
+        // Start two auctions
+        let time1 = Utc::now().naive_utc();
+        let auction1 = auction_at(EventId::new_test(0, 0, 1), time1, location);
+        repo.save(auction1.clone()).await?;
+        let other = auction_at(EventId::new_test(0, 0, 2), time1, other_location);
+        repo.save(other.clone()).await?;
+
+        // Finish the first one an hour later
+        let time2 = time1 + chrono::Duration::hours(1);
+        let auction2 = AuctionModel {
+            id: EventId::new_test(0, 0, 3),
+            at: time2,
+            is_finished: true,
+            sold_at_price: Some(U256::from_str("5000").unwrap()),
+            ..auction1.clone()
+        };
+        repo.save(auction2.clone()).await?;
+
+        // Before it finished, both auctions are active
+        let active_at_time1 = repo.get_active_auctions(time1).await?;
+        assert_eq!(active_at_time1.len(), 2);
+
+        // Once it finished, only the other one is active
+        let active_at_time2 = repo.get_active_auctions(time2).await?;
+        assert_eq!(active_at_time2.len(), 1);
+        assert_eq!(active_at_time2[0].id, other.id);
+
+        // The finished auction still shows up in the full snapshot
+        let all_at_time2 = repo.get_all_at_time(time2).await?;
+        assert_eq!(all_at_time2.len(), 2);
+        let finished = all_at_time2
+            .iter()
+            .find(|a| a.location == location)
+            .unwrap();
+        assert_eq!(finished.id, auction2.id);
+        assert!(finished.is_finished);
+        assert_eq!(finished.sold_at_price, auction2.sold_at_price);
+
+        Ok(())
+    }
+}
@@ -4,4 +4,6 @@ pub enum Error {
     SqlError(#[from] sqlx::Error),
     #[error("Invalid ID: {0}")]
     InvalidId(#[from] chaindata_models::error::Error),
+    #[error("Missing data of event {0}")]
+    MissingEventData(String),
 }
@@ -1,7 +1,75 @@
+use std::collections::HashMap;
+
 use crate::{events::base::EventDataRepository, Database, Error};
-use chaindata_models::events::{Event, EventId, EventType, FetchedEvent};
-use chrono::{DateTime, Utc};
-use sqlx::{query, query_as};
+use chaindata_models::{
+    events::{Event, EventId, EventType, FetchedEvent},
+    shared::Location,
+};
+use chrono::NaiveDateTime;
+use sqlx::{query, query_as, Postgres, QueryBuilder};
+
+/// Filters applied to the event feed. Every filter that is set must match.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub event_type: Option<EventType>,
+    /// Matches the buyer or seller of a land or auction, and the owner of a nuked land.
+    pub address: Option<String>,
+    pub location: Option<Location>,
+    /// Only events at or after this time.
+    pub from: Option<NaiveDateTime>,
+    /// Only events at or before this time.
+    pub to: Option<NaiveDateTime>,
+}
+
+impl EventFilter {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        if let Some(event_type) = &self.event_type {
+            query
+                .push(" AND event_type = ")
+                .push_bind(event_type.clone());
+        }
+        if let Some(from) = self.from {
+            query.push(" AND at >= ").push_bind(from);
+        }
+        if let Some(to) = self.to {
+            query.push(" AND at <= ").push_bind(to);
+        }
+        if let Some(address) = &self.address {
+            query
+                .push(" AND (EXISTS (SELECT 1 FROM event_land_bought d WHERE d.id = event.id AND (d.buyer = ")
+                .push_bind(address.clone())
+                .push(" OR d.seller = ")
+                .push_bind(address.clone())
+                .push(")) OR EXISTS (SELECT 1 FROM event_auction_finished d WHERE d.id = event.id AND d.buyer = ")
+                .push_bind(address.clone())
+                .push(") OR EXISTS (SELECT 1 FROM event_land_nuked d WHERE d.id = event.id AND d.owner = ")
+                .push_bind(address.clone())
+                .push("))");
+        }
+        if let Some(location) = self.location {
+            query.push(" AND (");
+            for (i, table) in [
+                "event_auction_finished",
+                "event_land_bought",
+                "event_land_nuked",
+                "event_new_auction",
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                if i > 0 {
+                    query.push(" OR ");
+                }
+                query
+                    .push("EXISTS (SELECT 1 FROM ")
+                    .push(table)
+                    .push(" d WHERE d.id = event.id AND d.location = ")
+                    .push_bind(location);
+            }
+            query.push(")");
+        }
+    }
+}
 
 pub struct Repository {
     db: Database,
@@ -37,25 +105,21 @@ impl Repository {
         .await?)
     }
 
-    /// Get the last event date.
+    /// Get the id of the last event, if there is one.
     ///
     /// # Errors
-    /// Returns an error if the event could not be fetched. Could be one of the following reasons:
-    /// - No row was found
-    /// - Error connecting to the database
-    /// - Wrong format of id
-    pub async fn get_last_event_date(&self) -> Result<DateTime<Utc>, Error> {
+    /// Returns an error if the database operation fails.
+    pub async fn get_last_event_id(&self) -> Result<Option<EventId>, Error> {
         Ok(query!(
             r#"
             SELECT
-                MAX(at)
+                MAX(id) as "id: EventId"
             FROM event
         "#
         )
         .fetch_one(&mut *(self.db.acquire().await?))
         .await?
-        .max
-        .map_or(DateTime::UNIX_EPOCH, |date| date.and_utc()))
+        .id)
     }
 
     /// Saves an event into the database.
@@ -97,4 +161,169 @@ impl Repository {
 
         Ok(id)
     }
+
+    /// Gets the events matching the filter, with their data, ordered by id.
+    ///
+    /// Pagination is done on the event id: `after` is the id of the last event of the previous
+    /// page.
+    ///
+    /// # Errors
+    /// Returns an error if the events could not be fetched, or if the data of an event is missing.
+    pub async fn get_events(
+        &self,
+        filter: &EventFilter,
+        after: Option<EventId>,
+        limit: i64,
+    ) -> Result<Vec<FetchedEvent>, Error> {
+        let mut conn = self.db.acquire().await?;
+
+        let mut query = QueryBuilder::new("SELECT id, at, event_type FROM event WHERE TRUE");
+        filter.push_conditions(&mut query);
+        // Ids are fixed-width strings, so they must be compared byte by byte to keep the order of
+        // `EventId`, whatever the collation of the database.
+        if let Some(after) = after {
+            query.push(r#" AND id COLLATE "C" > "#).push_bind(after);
+        }
+        query
+            .push(r#" ORDER BY id COLLATE "C" LIMIT "#)
+            .push_bind(limit);
+
+        let events: Vec<Event> = query.build_query_as().fetch_all(&mut *conn).await?;
+
+        // Fetch the data of the events, with one query per type
+        let mut ids_by_type: Vec<(EventType, Vec<EventId>)> = Vec::new();
+        for event in &events {
+            match ids_by_type
+                .iter_mut()
+                .find(|(event_type, _)| *event_type == event.event_type)
+            {
+                Some((_, ids)) => ids.push(event.id.clone()),
+                None => ids_by_type.push((event.event_type.clone(), vec![event.id.clone()])),
+            }
+        }
+
+        let mut data = HashMap::new();
+        for (event_type, ids) in ids_by_type {
+            for model in EventDataRepository::get_all_by_ids(&mut *conn, &event_type, &ids).await? {
+                if let Some(id) = model.id() {
+                    data.insert(id.as_string(), model);
+                }
+            }
+        }
+
+        events
+            .into_iter()
+            .map(|event| {
+                let data = data
+                    .remove(&event.id.as_string())
+                    .ok_or_else(|| Error::MissingEventData(event.id.as_string()))?;
+                Ok(FetchedEvent {
+                    id: event.id,
+                    at: event.at,
+                    data,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use chaindata_models::{
+        events::{
+            actions::{LandBoughtEventModel, NewAuctionEventModel},
+            EventDataModel,
+        },
+        shared::U256,
+    };
+    use chrono::DateTime;
+    use migrations::MIGRATOR;
+
+    fn land_bought(block: u64, location: u64, buyer: &str) -> FetchedEvent {
+        FetchedEvent {
+            id: EventId::new_test(block, 0, 0),
+            at: DateTime::from_timestamp(i64::try_from(block).unwrap(), 0)
+                .unwrap()
+                .naive_utc(),
+            data: EventDataModel::LandBought(LandBoughtEventModel {
+                id: None,
+                location: Location::new(location),
+                buyer: buyer.to_string(),
+                seller: "0x1".to_string(),
+                price: U256::from_str("100").unwrap(),
+                token_used: "0x2".to_string(),
+            }),
+        }
+    }
+
+    fn new_auction(block: u64, location: u64) -> FetchedEvent {
+        FetchedEvent {
+            id: EventId::new_test(block, 0, 0),
+            at: DateTime::from_timestamp(i64::try_from(block).unwrap(), 0)
+                .unwrap()
+                .naive_utc(),
+            data: EventDataModel::NewAuction(NewAuctionEventModel {
+                id: None,
+                location: Location::new(location),
+                starting_price: U256::from_str("1000").unwrap(),
+                floor_price: U256::from_str("10").unwrap(),
+            }),
+        }
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_get_events(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+        assert_eq!(repo.get_last_event_id().await?, None);
+
+        repo.save_event(new_auction(1, 10)).await?;
+        repo.save_event(land_bought(2, 10, "0xa")).await?;
+        repo.save_event(land_bought(4, 12, "0xa")).await?;
+        repo.save_event(land_bought(3, 11, "0xb")).await?;
+        assert_eq!(
+            repo.get_last_event_id().await?,
+            Some(EventId::new_test(4, 0, 0))
+        );
+
+        // Keyset pagination over every event
+        let first = repo.get_events(&EventFilter::default(), None, 3).await?;
+        assert_eq!(first.len(), 3);
+        assert_eq!(first[0].id, EventId::new_test(1, 0, 0));
+        assert!(matches!(first[0].data, EventDataModel::NewAuction(_)));
+        let second = repo
+            .get_events(&EventFilter::default(), Some(first[2].id.clone()), 3)
+            .await?;
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].id, EventId::new_test(4, 0, 0));
+
+        // Filters
+        let by_address = EventFilter {
+            address: Some("0xa".to_string()),
+            ..EventFilter::default()
+        };
+        let events = repo.get_events(&by_address, None, 10).await?;
+        assert_eq!(events.len(), 2);
+
+        let by_location = EventFilter {
+            location: Some(Location::new(10)),
+            event_type: Some(EventType::LandBought),
+            ..EventFilter::default()
+        };
+        let events = repo.get_events(&by_location, None, 10).await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, EventId::new_test(2, 0, 0));
+
+        let by_time = EventFilter {
+            from: DateTime::from_timestamp(2, 0).map(|at| at.naive_utc()),
+            to: DateTime::from_timestamp(3, 0).map(|at| at.naive_utc()),
+            ..EventFilter::default()
+        };
+        let events = repo.get_events(&by_time, None, 10).await?;
+        assert_eq!(events.len(), 2);
+
+        Ok(())
+    }
 }
@@ -1,5 +1,5 @@
-use chaindata_models::events::EventDataModel;
-use sqlx::Error;
+use chaindata_models::events::{EventDataModel, EventId, EventType};
+use sqlx::{postgres::PgRow, Error, FromRow};
 
 use super::event_data::EventModelRepository;
 
@@ -26,4 +26,53 @@ impl EventDataRepository {
         }
         .await
     }
+
+    /// Gets the data of several events of the same type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub async fn get_all_by_ids<'e, Conn>(
+        conn: Conn,
+        event_type: &EventType,
+        ids: &[EventId],
+    ) -> Result<Vec<EventDataModel>, Error>
+    where
+        Conn: 'e + sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        match event_type {
+            EventType::AuctionFinished => {
+                Self::get_models(conn, ids, EventDataModel::AuctionFinished).await
+            }
+            EventType::LandBought => Self::get_models(conn, ids, EventDataModel::LandBought).await,
+            EventType::LandNuked => Self::get_models(conn, ids, EventDataModel::LandNuked).await,
+            EventType::NewAuction => Self::get_models(conn, ids, EventDataModel::NewAuction).await,
+            EventType::AddressAuthorized => {
+                Self::get_models(conn, ids, EventDataModel::AddressAuthorized).await
+            }
+            EventType::AddressRemoved => {
+                Self::get_models(conn, ids, EventDataModel::AddressRemoved).await
+            }
+            EventType::VerifierUpdated => {
+                Self::get_models(conn, ids, EventDataModel::VerifierUpdated).await
+            }
+        }
+    }
+
+    async fn get_models<'e, Conn, Model>(
+        conn: Conn,
+        ids: &[EventId],
+        wrap: fn(Model) -> EventDataModel,
+    ) -> Result<Vec<EventDataModel>, Error>
+    where
+        Conn: 'e + sqlx::Executor<'e, Database = sqlx::Postgres>,
+        Model: Sized + Unpin + Send + Sync + for<'r> FromRow<'r, PgRow> + 'static,
+        Self: EventModelRepository<Model>,
+    {
+        Ok(Self::get_by_ids(conn, ids)
+            .await?
+            .into_iter()
+            .map(wrap)
+            .collect())
+    }
 }
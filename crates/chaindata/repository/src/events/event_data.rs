@@ -30,12 +30,31 @@ where
         query
             .push(" FROM ")
             .push(Self::TABLE_NAME)
-            .push(" WHERE id = ?")
+            .push(" WHERE id = ")
             .push_bind(id);
 
         query.build_query_as().fetch_optional(conn).await
     }
 
+    async fn get_by_ids<'e, Conn>(conn: Conn, ids: &[EventId]) -> Result<Vec<Model>, Error>
+    where
+        Conn: 'e + sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        let ids: Vec<String> = ids.iter().map(EventId::as_string).collect();
+
+        let mut query = QueryBuilder::new("SELECT ");
+        Self::push_parameters(&mut query);
+
+        query
+            .push(" FROM ")
+            .push(Self::TABLE_NAME)
+            .push(" WHERE id = ANY(")
+            .push_bind(ids)
+            .push(")");
+
+        query.build_query_as().fetch_all(conn).await
+    }
+
     fn push_parameters(query: &mut QueryBuilder<'_, sqlx::Postgres>);
     fn push_tuple(args: Separated<'_, '_, sqlx::Postgres, &'static str>, model: &Model);
 
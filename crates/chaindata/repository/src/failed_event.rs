@@ -0,0 +1,97 @@
+use chaindata_models::models::FailedEventModel;
+use sqlx::{query, query_as};
+
+use crate::{Database, Error};
+
+pub struct Repository {
+    db: Database,
+}
+
+impl Repository {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Saves a failed event in the dead-letter table, returning `false` if it was already saved.
+    ///
+    /// An event that already failed is kept as is, as torii can return it multiple times.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn save(&self, failed_event: FailedEventModel) -> Result<bool, Error> {
+        let result = query!(
+            r#"
+            INSERT INTO failed_event (stream, event_id, name, data, error, failed_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (stream, event_id) DO NOTHING
+            "#,
+            failed_event.stream,
+            failed_event.event_id,
+            failed_event.name,
+            failed_event.data,
+            failed_event.error,
+            failed_event.failed_at
+        )
+        .execute(&mut *(self.db.acquire().await?))
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Gets all the failed events of a stream, ordered by event id.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_by_stream(&self, stream: &str) -> Result<Vec<FailedEventModel>, sqlx::Error> {
+        query_as!(
+            FailedEventModel,
+            r#"
+            SELECT stream, event_id, name, data, error, failed_at
+            FROM failed_event
+            WHERE stream = $1
+            ORDER BY event_id
+            "#,
+            stream
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use migrations::MIGRATOR;
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_save_failed_event_once(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+
+        let failed_event = FailedEventModel {
+            stream: "events".to_string(),
+            event_id: "0x01:0x02:0x03".to_string(),
+            name: "ponzi_land-Unknown".to_string(),
+            data: "{}".to_string(),
+            error: "Unknown variant".to_string(),
+            failed_at: Utc::now().naive_utc(),
+        };
+        assert!(repo.save(failed_event.clone()).await?);
+
+        // Saving it again keeps the first failure
+        assert!(
+            !repo
+                .save(FailedEventModel {
+                    error: "Another error".to_string(),
+                    ..failed_event.clone()
+                })
+                .await?
+        );
+
+        assert_eq!(repo.get_by_stream("events").await?, vec![failed_event]);
+        assert!(repo.get_by_stream("models").await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
 use crate::{Database, Error};
-use chaindata_models::{events::EventId, models::LandModel, shared::Location};
+use chaindata_models::{
+    events::EventId,
+    models::{LandHistoryModel, LandModel},
+    shared::Location,
+};
 use chrono::NaiveDateTime;
 use sqlx::{query, query_as};
 use std::collections::HashMap;
@@ -107,6 +111,45 @@ impl Repository {
         .await
     }
 
+    /// Gets all lands owned by an address at a specific point in time
+    ///
+    /// # Errors
+    /// Returns an error if the lands could not be retrieved
+    pub async fn get_owned_by(
+        &self,
+        owner: &str,
+        at: NaiveDateTime,
+    ) -> Result<Vec<LandModel>, sqlx::Error> {
+        query_as!(
+            LandModel,
+            r#"
+            WITH latest_lands AS (
+                SELECT DISTINCT ON (location)
+                    id, at, location, bought_at, owner, sell_price, token_used, level
+                FROM land
+                WHERE at <= $2
+                ORDER BY location, at DESC
+            )
+            SELECT
+                id as "id: _",
+                at,
+                location as "location: Location",
+                bought_at,
+                owner,
+                sell_price as "sell_price: _",
+                token_used,
+                level as "level: _"
+            FROM latest_lands
+            WHERE owner = $1
+            ORDER BY location
+            "#,
+            owner,
+            at
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
     /// Gets a land model by ID
     ///
     /// # Errors
@@ -149,6 +192,84 @@ impl Repository {
         .map(|row| row.latest_time)
     }
 
+    /// Gets the id of the latest event from the land table
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest_event_id(&self) -> Result<Option<EventId>, sqlx::Error> {
+        query!(
+            r#"
+            SELECT MAX(id) as "latest_id: EventId"
+            FROM land
+            "#
+        )
+        .fetch_one(&mut *(self.db.acquire().await?))
+        .await
+        .map(|row| row.latest_id)
+    }
+
+    /// Gets the history of a land, with one entry each time the land or its stake changed,
+    /// ordered from the oldest to the most recent.
+    ///
+    /// The history can be bounded in time (inclusive), and is paginated with `limit` and `offset`.
+    ///
+    /// # Errors
+    /// Returns an error if the history could not be retrieved
+    pub async fn get_history(
+        &self,
+        location: Location,
+        from: Option<NaiveDateTime>,
+        to: Option<NaiveDateTime>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<LandHistoryModel>, sqlx::Error> {
+        query_as!(
+            LandHistoryModel,
+            r#"
+            WITH changes AS (
+                SELECT at FROM land WHERE location = $1
+                UNION
+                SELECT at FROM land_stake WHERE location = $1
+            )
+            SELECT
+                c.at as "at!",
+                l.owner as "owner?",
+                l.bought_at as "bought_at?",
+                l.sell_price as "sell_price?: _",
+                l.token_used as "token_used?",
+                l.level as "level?: _",
+                s.amount as "stake_amount?: _",
+                s.last_pay_time as "last_pay_time?"
+            FROM changes c
+            LEFT JOIN LATERAL (
+                SELECT owner, bought_at, sell_price, token_used, level
+                FROM land
+                WHERE location = $1 AND at <= c.at
+                ORDER BY at DESC
+                LIMIT 1
+            ) l ON true
+            LEFT JOIN LATERAL (
+                SELECT amount, last_pay_time
+                FROM land_stake
+                WHERE location = $1 AND at <= c.at
+                ORDER BY at DESC
+                LIMIT 1
+            ) s ON true
+            WHERE ($2::timestamp IS NULL OR c.at >= $2)
+                AND ($3::timestamp IS NULL OR c.at <= $3)
+            ORDER BY c.at
+            LIMIT $4 OFFSET $5
+            "#,
+            location as Location,
+            from,
+            to,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
     /// Gets the total distribution of tokens for all lands
     ///
     /// # Errors
@@ -310,4 +431,125 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_get_owned_by(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+
+        let time1 = Utc::now().naive_utc();
+        let time2 = time1 + chrono::Duration::hours(1);
+        let land = |location: u64, block: u64, owner: &str, at| LandModel {
+            id: EventId::new_test(block, 0, 0),
+            at,
+            location: location.into(),
+            bought_at: at,
+            owner: owner.to_string(),
+            sell_price: U256::from_str("100").unwrap(),
+            token_used: "0xtoken".to_string(),
+            level: Level::Zero,
+        };
+
+        repo.save(land(1, 1, "0xalice", time1)).await?;
+        repo.save(land(2, 2, "0xalice", time1)).await?;
+        repo.save(land(3, 3, "0xbob", time1)).await?;
+        // Alice sold her second land to Bob
+        repo.save(land(2, 4, "0xbob", time2)).await?;
+
+        let owned = repo.get_owned_by("0xalice", time1).await?;
+        assert_eq!(
+            owned.iter().map(|l| l.location).collect::<Vec<_>>(),
+            vec![1.into(), 2.into()]
+        );
+
+        let owned = repo.get_owned_by("0xalice", time2).await?;
+        assert_eq!(
+            owned.iter().map(|l| l.location).collect::<Vec<_>>(),
+            vec![1.into()]
+        );
+
+        let owned = repo.get_owned_by("0xbob", time2).await?;
+        assert_eq!(
+            owned.iter().map(|l| l.location).collect::<Vec<_>>(),
+            vec![2.into(), 3.into()]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_land_history(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool.clone());
+        let stake_repo = crate::LandStakeRepository::new(pool);
+
+        let location: Location = 4242.into();
+        // Whole seconds, as the database truncates timestamps to microseconds
+        let time1 = chrono::DateTime::from_timestamp(1_700_000_000, 0)
+            .unwrap()
+            .naive_utc();
+        let time2 = time1 + chrono::Duration::minutes(10);
+        let time3 = time1 + chrono::Duration::minutes(20);
+
+        let land1 = LandModel {
+            id: EventId::new_test(1, 0, 0),
+            at: time1,
+            location,
+            bought_at: time1,
+            owner: "0xowner1".to_string(),
+            sell_price: U256::from_str("100").unwrap(),
+            token_used: "0xtoken1".to_string(),
+            level: Level::Zero,
+        };
+        repo.save(land1.clone()).await?;
+        stake_repo
+            .save(chaindata_models::models::LandStakeModel {
+                id: EventId::new_test(2, 0, 0),
+                at: time2,
+                location,
+                last_pay_time: time2,
+                amount: U256::from_str("50").unwrap(),
+            })
+            .await?;
+        repo.save(LandModel {
+            id: EventId::new_test(3, 0, 0),
+            at: time3,
+            owner: "0xowner2".to_string(),
+            sell_price: U256::from_str("200").unwrap(),
+            ..land1.clone()
+        })
+        .await?;
+
+        let history = repo.get_history(location, None, None, 10, 0).await?;
+        assert_eq!(history.len(), 3);
+
+        // The land existed before its stake
+        assert_eq!(history[0].owner.as_deref(), Some("0xowner1"));
+        assert!(history[0].stake_amount.is_none());
+
+        // The stake changed, the land did not
+        assert_eq!(history[1].owner.as_deref(), Some("0xowner1"));
+        assert_eq!(history[1].stake_amount, Some(U256::from_str("50").unwrap()));
+
+        // The land was bought, keeping the previous stake
+        assert_eq!(history[2].owner.as_deref(), Some("0xowner2"));
+        assert_eq!(history[2].sell_price, Some(U256::from_str("200").unwrap()));
+        assert_eq!(history[2].stake_amount, Some(U256::from_str("50").unwrap()));
+
+        // Time bounds and pagination
+        let bounded = repo
+            .get_history(location, Some(time2), Some(time2), 10, 0)
+            .await?;
+        assert_eq!(bounded.len(), 1);
+        assert_eq!(bounded[0].at, time2);
+
+        let page = repo.get_history(location, None, None, 2, 2).await?;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].at, time3);
+
+        assert!(repo
+            .get_history(5678.into(), None, None, 10, 0)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
 }
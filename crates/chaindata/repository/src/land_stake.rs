@@ -143,6 +143,22 @@ impl Repository {
         .await
         .map(|row| row.latest_time)
     }
+
+    /// Gets the id of the latest event from the `land_stake` table
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest_event_id(&self) -> Result<Option<EventId>, sqlx::Error> {
+        query!(
+            r#"
+            SELECT MAX(id) as "latest_id: EventId"
+            FROM land_stake
+            "#
+        )
+        .fetch_one(&mut *(self.db.acquire().await?))
+        .await
+        .map(|row| row.latest_id)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,407 @@
+use chaindata_models::models::{LandSaleModel, LeaderboardEntryModel, LeaderboardKind};
+use chrono::NaiveDateTime;
+use sqlx::{query, query_as};
+
+use crate::{Database, Error};
+
+pub struct Repository {
+    db: Database,
+}
+
+impl Repository {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Saves a snapshot of a leaderboard.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn save(&self, entries: &[LeaderboardEntryModel]) -> Result<(), Error> {
+        let mut tx = self.db.begin().await?;
+
+        for entry in entries {
+            query!(
+                r#"
+                INSERT INTO leaderboard_entry (kind, computed_at, rank, address, score)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+                entry.kind as LeaderboardKind,
+                entry.computed_at,
+                entry.rank,
+                entry.address,
+                entry.score
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Thins out the snapshots of a leaderboard computed before `before`, only keeping the last
+    /// snapshot of each day.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn thin_out(
+        &self,
+        kind: LeaderboardKind,
+        before: NaiveDateTime,
+    ) -> Result<u64, Error> {
+        let result = query!(
+            r#"
+            DELETE FROM leaderboard_entry
+            WHERE kind = $1
+                AND computed_at < $2
+                AND computed_at NOT IN (
+                    SELECT MAX(computed_at)
+                    FROM leaderboard_entry
+                    WHERE kind = $1
+                    GROUP BY computed_at::date
+                )
+            "#,
+            kind as LeaderboardKind,
+            before
+        )
+        .execute(&mut *(self.db.acquire().await?))
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Gets the last snapshot of a leaderboard, ordered by rank.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest(
+        &self,
+        kind: LeaderboardKind,
+    ) -> Result<Vec<LeaderboardEntryModel>, sqlx::Error> {
+        query_as!(
+            LeaderboardEntryModel,
+            r#"
+            SELECT
+                kind as "kind: _",
+                computed_at,
+                rank,
+                address,
+                score
+            FROM leaderboard_entry
+            WHERE kind = $1
+                AND computed_at = (
+                    SELECT MAX(computed_at) FROM leaderboard_entry WHERE kind = $1
+                )
+            ORDER BY rank
+            "#,
+            kind as LeaderboardKind
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Gets the most recent snapshots of a leaderboard computed between `from` and `to`
+    /// (inclusive), ordered by time then rank.
+    ///
+    /// At most `limit` snapshots are returned.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_history(
+        &self,
+        kind: LeaderboardKind,
+        from: Option<NaiveDateTime>,
+        to: Option<NaiveDateTime>,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardEntryModel>, sqlx::Error> {
+        query_as!(
+            LeaderboardEntryModel,
+            r#"
+            SELECT
+                kind as "kind: _",
+                computed_at,
+                rank,
+                address,
+                score
+            FROM leaderboard_entry
+            WHERE kind = $1
+                AND computed_at IN (
+                    SELECT DISTINCT computed_at
+                    FROM leaderboard_entry
+                    WHERE kind = $1
+                        AND ($2::timestamp IS NULL OR computed_at >= $2)
+                        AND ($3::timestamp IS NULL OR computed_at <= $3)
+                    ORDER BY computed_at DESC
+                    LIMIT $4
+                )
+            ORDER BY computed_at, rank
+            "#,
+            kind as LeaderboardKind,
+            from,
+            to,
+            limit
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Counts the lands nuked of each owner.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_nuked_counts(&self) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        Ok(query!(
+            r#"
+            SELECT owner, COUNT(*) as "count!"
+            FROM event_land_nuked
+            GROUP BY owner
+            "#
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await?
+        .into_iter()
+        .map(|row| (row.owner, row.count))
+        .collect())
+    }
+
+    /// Counts the auctions won by each buyer.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_auction_wins(&self) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        Ok(query!(
+            r#"
+            SELECT buyer, COUNT(*) as "count!"
+            FROM event_auction_finished
+            GROUP BY buyer
+            "#
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await?
+        .into_iter()
+        .map(|row| (row.buyer, row.count))
+        .collect())
+    }
+
+    /// Gets every sale of a land by a player, along with the price the seller paid for it.
+    ///
+    /// The purchase of the seller is the previous acquisition of the land, bought from a player
+    /// or won at an auction. Sales without a matching purchase are not returned.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_land_sales(&self) -> Result<Vec<LandSaleModel>, sqlx::Error> {
+        query_as!(
+            LandSaleModel,
+            r#"
+            WITH acquisition AS (
+                SELECT id, location, buyer, price, token_used
+                FROM event_land_bought
+                UNION ALL
+                SELECT id, location, buyer, price, NULL
+                FROM event_auction_finished
+            ),
+            with_previous AS (
+                SELECT
+                    acquisition.id,
+                    event.at,
+                    LAG(buyer) OVER location_window AS previous_buyer,
+                    LAG(price) OVER location_window AS previous_price,
+                    LAG(token_used) OVER location_window AS previous_token,
+                    LAG(event.at) OVER location_window AS previous_at
+                FROM acquisition
+                JOIN event ON event.id = acquisition.id
+                WINDOW location_window AS (
+                    PARTITION BY location ORDER BY acquisition.id COLLATE "C"
+                )
+            )
+            SELECT
+                sale.seller,
+                with_previous.at as "sold_at!",
+                sale.price as "sell_price!: _",
+                sale.token_used as "sell_token!",
+                with_previous.previous_at as "bought_at!",
+                with_previous.previous_price as "buy_price!: _",
+                with_previous.previous_token as buy_token
+            FROM event_land_bought sale
+            JOIN with_previous ON with_previous.id = sale.id
+            WHERE with_previous.previous_buyer = sale.seller
+            "#
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::EventRepository;
+    use chaindata_models::{
+        events::{
+            actions::{AuctionFinishedEventModel, LandBoughtEventModel},
+            EventDataModel, EventId, FetchedEvent,
+        },
+        shared::{Location, U256},
+    };
+    use chrono::DateTime;
+    use migrations::MIGRATOR;
+    use sqlx::types::BigDecimal;
+
+    fn event(block: u64, data: EventDataModel) -> FetchedEvent {
+        FetchedEvent {
+            id: EventId::new_test(block, 0, 0),
+            at: DateTime::UNIX_EPOCH.naive_utc(),
+            data,
+        }
+    }
+
+    fn land_bought(block: u64, buyer: &str, seller: &str, price: &str) -> FetchedEvent {
+        event(
+            block,
+            EventDataModel::LandBought(LandBoughtEventModel {
+                id: None,
+                location: Location::new(1),
+                buyer: buyer.to_string(),
+                seller: seller.to_string(),
+                price: U256::from_str(price).unwrap(),
+                token_used: "0x1".to_string(),
+            }),
+        )
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_get_land_sales(pool: sqlx::PgPool) -> Result<(), Error> {
+        let events = EventRepository::new(pool.clone());
+        let repo = Repository::new(pool);
+
+        events
+            .save_event(event(
+                1,
+                EventDataModel::AuctionFinished(AuctionFinishedEventModel {
+                    id: None,
+                    location: Location::new(1),
+                    buyer: "0xa".to_string(),
+                    price: U256::from_str("10").unwrap(),
+                }),
+            ))
+            .await?;
+        events
+            .save_event(land_bought(2, "0xb", "0xa", "30"))
+            .await?;
+        events
+            .save_event(land_bought(3, "0xc", "0xb", "20"))
+            .await?;
+
+        let mut sales = repo.get_land_sales().await?;
+        sales.sort_by(|a, b| a.seller.cmp(&b.seller));
+        assert_eq!(sales.len(), 2);
+
+        // Won at an auction, so bought in the main token
+        assert_eq!(sales[0].seller, "0xa");
+        assert_eq!(sales[0].sell_price, U256::from_str("30").unwrap());
+        assert_eq!(sales[0].buy_price, U256::from_str("10").unwrap());
+        assert_eq!(sales[0].buy_token, None);
+
+        assert_eq!(sales[1].seller, "0xb");
+        assert_eq!(sales[1].buy_price, U256::from_str("30").unwrap());
+        assert_eq!(sales[1].buy_token.as_deref(), Some("0x1"));
+
+        let wins = repo.get_auction_wins().await?;
+        assert_eq!(wins, vec![("0xa".to_string(), 1)]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_save_and_get_snapshots(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+
+        let snapshot = |at: i64, scores: &[i64]| -> Vec<LeaderboardEntryModel> {
+            scores
+                .iter()
+                .zip(1..)
+                .map(|(score, rank)| LeaderboardEntryModel {
+                    kind: LeaderboardKind::LandsHeld,
+                    computed_at: DateTime::from_timestamp(at, 0).unwrap().naive_utc(),
+                    rank,
+                    address: format!("0x{rank}"),
+                    score: BigDecimal::from(*score),
+                })
+                .collect()
+        };
+
+        repo.save(&snapshot(100, &[5, 3])).await?;
+        repo.save(&snapshot(200, &[6, 4, 1])).await?;
+        repo.save(&snapshot(300, &[7])).await?;
+
+        assert_eq!(
+            repo.get_latest(LeaderboardKind::LandsHeld).await?,
+            snapshot(300, &[7])
+        );
+        assert!(repo
+            .get_latest(LeaderboardKind::LandValue)
+            .await?
+            .is_empty());
+
+        // Most recent snapshots in the range, oldest first
+        let history = repo
+            .get_history(
+                LeaderboardKind::LandsHeld,
+                DateTime::from_timestamp(100, 0).map(|at| at.naive_utc()),
+                None,
+                2,
+            )
+            .await?;
+        assert_eq!(
+            history,
+            [snapshot(200, &[6, 4, 1]), snapshot(300, &[7])].concat()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_thin_out(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+        let day = 86_400;
+
+        let snapshot = |kind: LeaderboardKind, at: i64| LeaderboardEntryModel {
+            kind,
+            computed_at: DateTime::from_timestamp(at, 0).unwrap().naive_utc(),
+            rank: 1,
+            address: "0x1".to_string(),
+            score: BigDecimal::from(1),
+        };
+        let times = [day, day + 900, day + 1800, 2 * day + 900, 2 * day + 1800];
+        for at in times {
+            repo.save(&[snapshot(LeaderboardKind::LandsHeld, at)])
+                .await?;
+            repo.save(&[snapshot(LeaderboardKind::LandValue, at)])
+                .await?;
+        }
+
+        // The second day is still kept in full
+        let before = DateTime::from_timestamp(2 * day, 0).unwrap().naive_utc();
+        assert_eq!(repo.thin_out(LeaderboardKind::LandsHeld, before).await?, 2);
+        assert_eq!(repo.thin_out(LeaderboardKind::LandsHeld, before).await?, 0);
+
+        let history = |kind| repo.get_history(kind, None, None, 10);
+        assert_eq!(
+            history(LeaderboardKind::LandsHeld).await?,
+            [day + 1800, 2 * day + 900, 2 * day + 1800]
+                .map(|at| snapshot(LeaderboardKind::LandsHeld, at))
+        );
+        // Other leaderboards are untouched
+        assert_eq!(
+            history(LeaderboardKind::LandValue).await?.len(),
+            times.len()
+        );
+
+        Ok(())
+    }
+}
@@ -1,12 +1,24 @@
+pub mod auction;
 pub mod event;
 pub mod events;
+pub mod failed_event;
 pub mod land;
 pub mod land_stake;
+pub mod leaderboard;
+pub mod sync_cursor;
+pub mod token;
+pub mod token_price;
 
 mod error;
 
 pub type Database = sqlx::PgPool;
+pub use auction::Repository as AuctionRepository;
 pub use error::Error;
-pub use event::Repository as EventRepository;
+pub use event::{EventFilter, Repository as EventRepository};
+pub use failed_event::Repository as FailedEventRepository;
 pub use land::Repository as LandRepository;
 pub use land_stake::Repository as LandStakeRepository;
+pub use leaderboard::Repository as LeaderboardRepository;
+pub use sync_cursor::Repository as SyncCursorRepository;
+pub use token::Repository as TokenRepository;
+pub use token_price::Repository as TokenPriceRepository;
@@ -0,0 +1,88 @@
+use chaindata_models::models::SyncCursorModel;
+use sqlx::{query, query_as};
+
+use crate::{Database, Error};
+
+pub struct Repository {
+    db: Database,
+}
+
+impl Repository {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Gets the cursor of a stream, if the stream was already synchronized once.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get(&self, stream: &str) -> Result<Option<SyncCursorModel>, sqlx::Error> {
+        query_as!(
+            SyncCursorModel,
+            r#"
+            SELECT stream, last_event_id, updated_at
+            FROM sync_cursor
+            WHERE stream = $1
+            "#,
+            stream
+        )
+        .fetch_optional(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Saves the cursor of a stream, replacing the previous one.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn save(&self, cursor: SyncCursorModel) -> Result<(), Error> {
+        query!(
+            r#"
+            INSERT INTO sync_cursor (stream, last_event_id, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (stream) DO UPDATE
+            SET last_event_id = EXCLUDED.last_event_id, updated_at = EXCLUDED.updated_at
+            "#,
+            cursor.stream,
+            cursor.last_event_id,
+            cursor.updated_at
+        )
+        .execute(&mut *(self.db.acquire().await?))
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use migrations::MIGRATOR;
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_save_and_get_cursor(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+
+        assert!(repo.get("events").await?.is_none());
+
+        let cursor = SyncCursorModel {
+            stream: "events".to_string(),
+            last_event_id: "0x01:0x02:0x03".to_string(),
+            updated_at: Utc::now().naive_utc(),
+        };
+        repo.save(cursor.clone()).await?;
+        assert_eq!(repo.get("events").await?, Some(cursor.clone()));
+
+        // Saving again moves the cursor, without touching the other streams
+        let moved = SyncCursorModel {
+            last_event_id: "0x02:0x01:0x00".to_string(),
+            ..cursor
+        };
+        repo.save(moved.clone()).await?;
+        assert_eq!(repo.get("events").await?, Some(moved));
+        assert!(repo.get("models").await?.is_none());
+
+        Ok(())
+    }
+}
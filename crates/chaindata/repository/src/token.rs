@@ -0,0 +1,129 @@
+use chaindata_models::models::TokenModel;
+use sqlx::{query, query_as};
+
+use crate::{Database, Error};
+
+pub struct Repository {
+    db: Database,
+}
+
+impl Repository {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Gets every token, in the order they were added.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_all(&self) -> Result<Vec<TokenModel>, sqlx::Error> {
+        query_as!(
+            TokenModel,
+            r#"
+            SELECT address, symbol, name, decimals, created_at
+            FROM token
+            ORDER BY created_at, address
+            "#
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Saves a token, replacing the symbol, name and decimals of a known address.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn save(&self, token: &TokenModel) -> Result<(), Error> {
+        query!(
+            r#"
+            INSERT INTO token (address, symbol, name, decimals, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (address) DO UPDATE
+            SET symbol = EXCLUDED.symbol, name = EXCLUDED.name, decimals = EXCLUDED.decimals
+            "#,
+            token.address,
+            token.symbol,
+            token.name,
+            token.decimals,
+            token.created_at
+        )
+        .execute(&mut *(self.db.acquire().await?))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes a token, returning whether it existed.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn delete(&self, address: &str) -> Result<bool, Error> {
+        let result = query!(
+            r#"
+            DELETE FROM token
+            WHERE address = $1
+            "#,
+            address
+        )
+        .execute(&mut *(self.db.acquire().await?))
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Timelike, Utc};
+    use migrations::MIGRATOR;
+
+    fn token(address: &str, symbol: &str, minutes: i64) -> TokenModel {
+        TokenModel {
+            address: address.to_string(),
+            symbol: symbol.to_string(),
+            name: None,
+            decimals: Some(18),
+            created_at: Utc::now().naive_utc().with_nanosecond(0).unwrap()
+                + Duration::minutes(minutes),
+        }
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_save_and_delete(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+        assert!(repo.get_all().await?.is_empty());
+
+        let strk = token("0x02", "STRK", 1);
+        let eth = token("0x01", "ETH", 2);
+        repo.save(&strk).await?;
+        repo.save(&eth).await?;
+        assert_eq!(repo.get_all().await?, vec![strk.clone(), eth.clone()]);
+
+        // Saving a known address updates it, without moving it
+        let renamed = TokenModel {
+            symbol: "sSTRK".to_string(),
+            name: Some("Staked STRK".to_string()),
+            created_at: eth.created_at,
+            ..strk.clone()
+        };
+        repo.save(&renamed).await?;
+        assert_eq!(
+            repo.get_all().await?,
+            vec![
+                TokenModel {
+                    created_at: strk.created_at,
+                    ..renamed
+                },
+                eth.clone()
+            ]
+        );
+
+        assert!(repo.delete("0x02").await?);
+        assert!(!repo.delete("0x02").await?);
+        assert_eq!(repo.get_all().await?, vec![eth]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use chaindata_models::{
+    models::{PriceCandleModel, PriceSampleModel, TokenPriceModel},
+    shared::U256,
+};
+use chrono::NaiveDateTime;
+use sqlx::{query, query_as};
+
+use crate::{Database, Error};
+
+pub struct Repository {
+    db: Database,
+}
+
+impl Repository {
+    #[must_use]
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Saves the prices of an update.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn save_all(&self, prices: &[TokenPriceModel]) -> Result<(), Error> {
+        let mut tx = self.db.begin().await?;
+
+        for price in prices {
+            query!(
+                r#"
+                INSERT INTO token_price (
+                    token, at, pool_token0, pool_token1, pool_fee, pool_tick_spacing,
+                    pool_extension, ratio
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+                price.token,
+                price.at,
+                price.pool_token0,
+                price.pool_token1,
+                price.pool_fee,
+                price.pool_tick_spacing,
+                price.pool_extension,
+                price.ratio as _
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Gets the last known price of each token.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest_all(&self) -> Result<Vec<TokenPriceModel>, sqlx::Error> {
+        query_as!(
+            TokenPriceModel,
+            r#"
+            SELECT DISTINCT ON (token)
+                token,
+                at,
+                pool_token0,
+                pool_token1,
+                pool_fee,
+                pool_tick_spacing,
+                pool_extension,
+                ratio as "ratio: _"
+            FROM token_price
+            ORDER BY token, at DESC
+            "#
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Gets the raw ratio of each token at the matching time, which is the last one saved at or
+    /// before that time.
+    ///
+    /// The result is in the same order as the input, with `None` when no price was known yet.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_ratios_at(
+        &self,
+        tokens: &[String],
+        at: &[NaiveDateTime],
+    ) -> Result<Vec<Option<U256>>, sqlx::Error> {
+        Ok(query!(
+            r#"
+            SELECT price.ratio as "ratio?: U256"
+            FROM UNNEST($1::text[], $2::timestamp[]) WITH ORDINALITY AS request(token, at, idx)
+            LEFT JOIN LATERAL (
+                SELECT ratio
+                FROM token_price
+                WHERE token_price.token = request.token
+                    AND token_price.at <= request.at
+                ORDER BY token_price.at DESC
+                LIMIT 1
+            ) price ON TRUE
+            ORDER BY request.idx
+            "#,
+            tokens,
+            at
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await?
+        .into_iter()
+        .map(|row| row.ratio)
+        .collect())
+    }
+
+    /// Gets the prices of all tokens saved after `from` and up to `to`, along with the last price
+    /// of each token saved at or before `from`, ordered by token then time.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_samples(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<PriceSampleModel>, sqlx::Error> {
+        query_as!(
+            PriceSampleModel,
+            r#"
+            SELECT token as "token!", at as "at!", ratio as "ratio!: _"
+            FROM (
+                SELECT token, at, ratio
+                FROM token_price
+                WHERE at > $1
+                    AND at <= $2
+                UNION ALL
+                (
+                    SELECT DISTINCT ON (token) token, at, ratio
+                    FROM token_price
+                    WHERE at <= $1
+                    ORDER BY token, at DESC
+                )
+            ) samples
+            ORDER BY token, at
+            "#,
+            from,
+            to
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+
+    /// Gets the prices of a token between `from` and `to` (inclusive), grouped in buckets of
+    /// `interval_seconds`, from the oldest to the most recent.
+    ///
+    /// Buckets are aligned on the unix epoch, and buckets without any price are not returned.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_candles(
+        &self,
+        token: &str,
+        interval_seconds: i64,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<PriceCandleModel>, sqlx::Error> {
+        query_as!(
+            PriceCandleModel,
+            r#"
+            SELECT
+                date_bin($2::bigint * INTERVAL '1 second', at, TIMESTAMP 'epoch') as "at!",
+                (array_agg(ratio ORDER BY at))[1] as "open!: _",
+                MAX(ratio) as "high!: _",
+                MIN(ratio) as "low!: _",
+                (array_agg(ratio ORDER BY at DESC))[1] as "close!: _"
+            FROM token_price
+            WHERE token = $1
+                AND at >= $3
+                AND at <= $4
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            token,
+            interval_seconds,
+            from,
+            to
+        )
+        .fetch_all(&mut *(self.db.acquire().await?))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use chrono::DateTime;
+    use migrations::MIGRATOR;
+
+    fn price(token: &str, at: i64, ratio: &str) -> TokenPriceModel {
+        TokenPriceModel {
+            token: token.to_string(),
+            at: DateTime::from_timestamp(at, 0).unwrap().naive_utc(),
+            pool_token0: "0x1".to_string(),
+            pool_token1: token.to_string(),
+            pool_fee: "170141183460469235328".to_string(),
+            pool_tick_spacing: 1000,
+            pool_extension: "0x0".to_string(),
+            ratio: U256::from_str(ratio).unwrap(),
+        }
+    }
+
+    #[sqlx::test(migrator = "MIGRATOR")]
+    async fn test_latest_and_candles(pool: sqlx::PgPool) -> Result<(), Error> {
+        let repo = Repository::new(pool);
+
+        repo.save_all(&[price("0xa", 0, "10"), price("0xb", 0, "100")])
+            .await?;
+        repo.save_all(&[price("0xa", 30, "30"), price("0xb", 30, "90")])
+            .await?;
+        repo.save_all(&[price("0xa", 60, "20")]).await?;
+        repo.save_all(&[price("0xa", 90, "5")]).await?;
+
+        let ratios = repo
+            .get_ratios_at(
+                &["0xa".to_string(), "0xa".to_string(), "0xc".to_string()],
+                &[
+                    DateTime::from_timestamp(45, 0).unwrap().naive_utc(),
+                    DateTime::from_timestamp(-1, 0).unwrap().naive_utc(),
+                    DateTime::from_timestamp(45, 0).unwrap().naive_utc(),
+                ],
+            )
+            .await?;
+        assert_eq!(
+            ratios,
+            vec![Some(U256::from_str("30").unwrap()), None, None]
+        );
+
+        let mut latest = repo.get_latest_all().await?;
+        latest.sort_by(|a, b| a.token.cmp(&b.token));
+        assert_eq!(latest, vec![price("0xa", 90, "5"), price("0xb", 30, "90")]);
+
+        let at = |seconds| DateTime::from_timestamp(seconds, 0).unwrap().naive_utc();
+        let ratio = |ratio| U256::from_str(ratio).unwrap();
+        let sample = |token: &str, seconds, value| PriceSampleModel {
+            token: token.to_string(),
+            at: at(seconds),
+            ratio: ratio(value),
+        };
+        assert_eq!(
+            repo.get_samples(at(45), at(90)).await?,
+            vec![
+                sample("0xa", 30, "30"),
+                sample("0xa", 60, "20"),
+                sample("0xa", 90, "5"),
+                sample("0xb", 30, "90"),
+            ]
+        );
+
+        let candles = repo.get_candles("0xa", 60, at(0), at(90)).await?;
+        assert_eq!(
+            candles,
+            vec![
+                PriceCandleModel {
+                    at: at(0),
+                    open: ratio("10"),
+                    high: ratio("30"),
+                    low: ratio("10"),
+                    close: ratio("30"),
+                },
+                PriceCandleModel {
+                    at: at(60),
+                    open: ratio("20"),
+                    high: ratio("20"),
+                    low: ratio("5"),
+                    close: ratio("5"),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
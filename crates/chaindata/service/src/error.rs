@@ -2,6 +2,10 @@ use torii_ingester::torii_client;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    #[error("Error while connecting to the database")]
+    #[error("Torii error: {0}")]
     ToriiConnectionError(#[from] torii_client::Error),
+    #[error("Database error: {0}")]
+    RepositoryError(#[from] chaindata_repository::Error),
+    #[error("SQL error: {0}")]
+    SqlError(#[from] sqlx::Error),
 }
@@ -2,28 +2,45 @@ pub mod error;
 pub mod gg_xyz_api;
 pub mod tasks;
 
-use chaindata_repository::{Database, EventRepository, LandRepository, LandStakeRepository};
+use chaindata_models::events::FetchedEvent;
+use chaindata_repository::{
+    AuctionRepository, Database, EventRepository, FailedEventRepository, LandRepository,
+    LandStakeRepository, SyncCursorRepository,
+};
+use chrono::{Duration, Utc};
 use gg_xyz_api::GGApi;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
 use std::sync::Arc;
 use tasks::{
-    event_listener::EventListenerTask, model_listener::ModelListenerTask, Task, TaskWrapper,
+    event_listener::EventListenerTask, model_listener::ModelListenerTask, status::TaskStatus, Task,
+    TaskWrapper,
 };
+use tokio::sync::broadcast;
 use torii_ingester::{ToriiClient, ToriiConfiguration};
 
+/// Maximum time without a successful synchronization before the service is considered stuck.
+pub const MAX_SYNC_AGE_SECONDS: i64 = 5 * 60;
+
+/// Number of new events kept for the subscribers that are lagging behind.
+const EVENTS_CHANNEL_CAPACITY: usize = 1024;
+
 /// `ChainDataService` is a service that handles the importation and syncing of new events and data
 /// to the database for further processing.
 pub struct ChainDataService {
     event_listener_task: TaskWrapper<EventListenerTask>,
     model_listener_task: TaskWrapper<ModelListenerTask>,
+    events_sender: broadcast::Sender<FetchedEvent>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ChainDataServiceConfiguration {
     pub torii_url: String,
     pub world_address: Felt,
+    /// Whether to use the gRPC subscription of torii to sync as soon as something is indexed,
+    /// instead of only polling.
+    pub torii_live_updates: bool,
     pub gg_xyz_enabled: bool,
     pub gg_xyz_api_key: String,
     pub gg_xyz_api_url: Url,
@@ -48,12 +65,20 @@ impl ChainDataService {
         let event_repository = Arc::new(EventRepository::new(database.clone()));
         let land_repository = Arc::new(LandRepository::new(database.clone()));
         let land_stake_repository = Arc::new(LandStakeRepository::new(database.clone()));
+        let auction_repository = Arc::new(AuctionRepository::new(database.clone()));
+        let sync_cursor_repository = Arc::new(SyncCursorRepository::new(database.clone()));
+        let failed_event_repository = Arc::new(FailedEventRepository::new(database.clone()));
         let gg_xyz_api = Arc::new(GGApi::new(&config.gg_xyz_api_url, config.gg_xyz_api_key));
+        let (events_sender, _) = broadcast::channel(EVENTS_CHANNEL_CAPACITY);
 
         Ok(Arc::new(Self {
             event_listener_task: EventListenerTask::new(
                 client.clone(),
                 event_repository,
+                events_sender.clone(),
+                sync_cursor_repository.clone(),
+                failed_event_repository.clone(),
+                config.torii_live_updates,
                 Some(gg_xyz_api).filter(|_| config.gg_xyz_enabled),
             )
             .wrap(),
@@ -61,8 +86,13 @@ impl ChainDataService {
                 client.clone(),
                 land_repository,
                 land_stake_repository,
+                auction_repository,
+                sync_cursor_repository,
+                failed_event_repository,
+                config.torii_live_updates,
             )
             .wrap(),
+            events_sender,
         }))
     }
 
@@ -76,4 +106,37 @@ impl ChainDataService {
         self.event_listener_task.start();
         self.model_listener_task.start();
     }
+
+    /// Subscribes to the events saved from now on.
+    ///
+    /// A subscriber that does not keep up misses the oldest events, and is notified with
+    /// [`broadcast::error::RecvError::Lagged`].
+    #[must_use]
+    pub fn subscribe_events(&self) -> broadcast::Receiver<FetchedEvent> {
+        self.events_sender.subscribe()
+    }
+
+    /// Returns the status of all the tasks of the service.
+    #[must_use]
+    pub fn status(&self) -> Vec<TaskStatus> {
+        vec![
+            self.event_listener_task.status(),
+            self.model_listener_task.status(),
+        ]
+    }
+
+    /// Whether all the tasks are running (a crashed task is not running until it is restarted).
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        self.status().iter().all(|status| status.running)
+    }
+
+    /// Whether all the tasks are running and synchronized recently, so the data can be trusted.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        let now = Utc::now();
+        self.status()
+            .iter()
+            .all(|status| status.is_healthy(Duration::seconds(MAX_SYNC_AGE_SECONDS), now))
+    }
 }
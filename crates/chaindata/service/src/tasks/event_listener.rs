@@ -1,24 +1,50 @@
 use std::sync::Arc;
 
-use chaindata_models::events::{EventDataModel, EventId, FetchedEvent};
-use chaindata_repository::event::Repository as EventRepository;
+use chaindata_models::{
+    events::{EventDataModel, EventId, FetchedEvent},
+    models::SyncCursorModel,
+};
+use chaindata_repository::{
+    event::Repository as EventRepository, FailedEventRepository, SyncCursorRepository,
+};
 use chrono::Utc;
 use ponziland_models::events::EventData;
 use sqlx::error::DatabaseError;
-use tokio::select;
+use tokio::{select, sync::broadcast};
 use tokio_stream::StreamExt;
 use torii_ingester::{RawToriiData, ToriiClient};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
-use crate::gg_xyz_api::{GGApi, PostRequest};
+use crate::{
+    error::Error,
+    gg_xyz_api::{GGApi, PostRequest},
+};
 
-use super::Task;
+use super::{
+    failed_events::save_failed_event,
+    live_updates::{LiveUpdates, POLLING_INTERVAL},
+    status::StatusHandle,
+    Task, ToriiStream,
+};
+
+/// Name of the stream in the `sync_cursor` table.
+const STREAM: &str = "events";
 
 /// `EventListenerTask` is a task that subscribes to the events of the on-chain indexer (torii),
 /// and pushes them to the local database.
+///
+/// The last processed torii event id is persisted as a sync cursor, so that the task resumes
+/// exactly where it stopped. If live updates are enabled, the gRPC subscription of torii is used
+/// to catch up as soon as something new is indexed (see [`LiveUpdates`]).
+///
+/// Every newly saved event is broadcast to the subscribers of the service.
 pub struct EventListenerTask {
     client: Arc<ToriiClient>,
     event_repository: Arc<EventRepository>,
+    events_sender: broadcast::Sender<FetchedEvent>,
+    sync_cursor_repository: Arc<SyncCursorRepository>,
+    failed_event_repository: Arc<FailedEventRepository>,
+    live_updates: bool,
     gg_api: Option<Arc<GGApi>>,
 }
 
@@ -26,27 +52,76 @@ impl EventListenerTask {
     pub fn new(
         client: Arc<ToriiClient>,
         event_repository: Arc<EventRepository>,
+        events_sender: broadcast::Sender<FetchedEvent>,
+        sync_cursor_repository: Arc<SyncCursorRepository>,
+        failed_event_repository: Arc<FailedEventRepository>,
+        live_updates: bool,
         gg_api: Option<Arc<GGApi>>,
     ) -> Self {
         Self {
             client,
             event_repository,
+            events_sender,
+            sync_cursor_repository,
+            failed_event_repository,
+            live_updates,
             gg_api,
         }
     }
 
-    async fn process_event(&self, event: RawToriiData) {
+    /// Opens the stream of events to process, starting from the sync cursor if there is one.
+    async fn events_stream(&self) -> Result<ToriiStream, Error> {
+        if let Some(cursor) = self.sync_cursor_repository.get(STREAM).await? {
+            info!("Polling for events from cursor: {}", cursor.last_event_id);
+
+            return Ok(Box::pin(
+                self.client
+                    .get_all_events_from_cursor(&cursor.last_event_id)?,
+            ));
+        }
+
+        // No cursor yet, start after the last event that we know of
+        if let Some(last_event_id) = self.event_repository.get_last_event_id().await? {
+            let last_event_id = last_event_id.to_torii_string();
+            info!(
+                "No sync cursor, polling for events after: {}",
+                last_event_id
+            );
+
+            return Ok(Box::pin(
+                self.client.get_all_events_from_cursor(&last_event_id)?,
+            ));
+        }
+
+        info!("No sync cursor nor events, polling for all events");
+
+        Ok(Box::pin(self.client.get_all_events()?))
+    }
+
+    /// Processes an event, returning `false` if it could not be saved and must be retried.
+    ///
+    /// Events that cannot be parsed are saved as failed events instead.
+    async fn process_event(&self, event: RawToriiData) -> bool {
         // Parse and save the event
         let event = match event {
             RawToriiData::Grpc(data) => {
                 debug!("Processing GRPC event");
 
-                FetchedEvent {
-                    id: EventId::new_test(0, 0, 0),
-                    at: Utc::now().naive_utc(),
-                    data: EventData::try_from(data)
-                        .expect("An error occurred while deserializing model")
-                        .into(),
+                match EventData::try_from(data.clone()) {
+                    Ok(parsed) => FetchedEvent {
+                        id: EventId::new_test(0, 0, 0),
+                        at: Utc::now().naive_utc(),
+                        data: parsed.into(),
+                    },
+                    Err(err) => {
+                        // Without an event id, it cannot be put in the dead-letter table. The
+                        // SQL catch-up reads the same event with its id, and puts it aside.
+                        error!(
+                            "Failed to process {} (no event id) from {STREAM} live updates: {err}, data: {data:?}",
+                            data.name
+                        );
+                        return true;
+                    }
                 }
             }
             RawToriiData::Json {
@@ -57,34 +132,57 @@ impl EventListenerTask {
             } => {
                 debug!("Processing JSON event");
 
-                FetchedEvent {
-                    id: EventId::parse_from_torii(&event_id).unwrap(),
-                    at: at.naive_utc(),
-                    data: EventData::from_json(&name, data.clone())
-                        .unwrap_or_else(|_| {
-                            panic!(
-                                "An error occurred while deserializing model for event {name}: {data:#?}"
-                            )
-                        })
-                        .into(),
+                let parsed = EventId::parse_from_torii(&event_id)
+                    .map_err(|err| err.to_string())
+                    .and_then(|id| {
+                        EventData::from_json(&name, data.clone())
+                            .map(|data| (id, data))
+                            .map_err(|err| err.to_string())
+                    });
+
+                match parsed {
+                    Ok((id, data)) => FetchedEvent {
+                        id,
+                        at: at.naive_utc(),
+                        data: data.into(),
+                    },
+                    Err(err) => {
+                        return save_failed_event(
+                            &self.failed_event_repository,
+                            STREAM,
+                            &name,
+                            &event_id,
+                            &data,
+                            err,
+                        )
+                        .await;
+                    }
                 }
             }
         };
 
-        if let Err(chaindata_repository::Error::SqlError(err)) =
-            self.event_repository.save_event(event.clone()).await
-        {
-            if !err
-                .as_database_error()
-                .is_some_and(DatabaseError::is_unique_violation)
+        match self.event_repository.save_event(event.clone()).await {
+            Ok(id) => {
+                info!("Successfully saved event!");
+
+                let mut saved = event.clone();
+                saved.data.set_id(id);
+                // Sending only fails when nobody is listening
+                let _ = self.events_sender.send(saved);
+            }
+            Err(chaindata_repository::Error::SqlError(err))
+                if err
+                    .as_database_error()
+                    .is_some_and(DatabaseError::is_unique_violation) =>
             {
+                // It is a duplicate, so ignore it
+                return true;
+            }
+            Err(err) => {
                 error!("Failed to save event: {}", err);
+                return false;
             }
-
-            // It is a duplicate, so ignore it
-            return;
         }
-        info!("Successfully saved event!");
 
         if let Some(gg_api) = &self.gg_api {
             // If the event is used to submit something to gg, send it.
@@ -119,6 +217,18 @@ impl EventListenerTask {
                 }
             }
         }
+
+        true
+    }
+
+    async fn save_cursor(&self, last_event_id: String) -> Result<(), chaindata_repository::Error> {
+        self.sync_cursor_repository
+            .save(SyncCursorModel {
+                stream: STREAM.to_string(),
+                last_event_id,
+                updated_at: Utc::now().naive_utc(),
+            })
+            .await
     }
 }
 
@@ -126,48 +236,107 @@ impl EventListenerTask {
 impl Task for EventListenerTask {
     const NAME: &'static str = "EventListenerTask";
 
-    async fn do_task(self: std::sync::Arc<Self>, mut rx: tokio::sync::oneshot::Receiver<()>) {
-        info!("Starting EventListenerTask with 10-second polling interval");
+    async fn do_task(
+        self: std::sync::Arc<Self>,
+        mut rx: tokio::sync::oneshot::Receiver<()>,
+        status: StatusHandle,
+    ) {
+        info!(
+            "Starting EventListenerTask (live updates: {}, polling interval: {:?})",
+            self.live_updates, POLLING_INTERVAL
+        );
 
-        loop {
-            // Poll for new events from the database
-            let last_check = self
-                .event_repository
-                .get_last_event_date()
-                .await
-                .expect("Failed to get last event date");
+        let mut live_updates = LiveUpdates::new(self.live_updates);
 
-            // Subtract 1 second to avoid missing events due to timestamp precision issues
-            let safe_last_check = last_check - chrono::Duration::seconds(1);
+        loop {
+            // Subscribe before the catch-up, so that no update is missed in between
+            if live_updates.needs_subscription() {
+                live_updates.subscribed(self.client.subscribe_events().await);
+            }
 
-            info!(
-                "Polling for events after: {:?} (with 1s safety buffer)",
-                safe_last_check
-            );
+            // Whether the whole catch-up succeeded
+            let mut completed = true;
 
-            // Get all events that occurred after the last check (with safety buffer)
-            let mut events_stream = self
-                .client
-                .get_all_events_after(safe_last_check)
-                .expect("Error while fetching events");
+            let mut events_stream = match self.events_stream().await {
+                Ok(events_stream) => events_stream,
+                Err(err) => {
+                    error!("Failed to open the events stream: {}", err);
+                    status.error(format!("Failed to open the events stream: {err}"));
+                    completed = false;
+                    Box::pin(tokio_stream::empty())
+                }
+            };
 
-            // Process events as they go
+            // Process events as they go, they are ordered by event id
             let mut event_count = 0;
+            let mut last_event_id = None;
             while let Some(event) = events_stream.next().await {
-                self.process_event(event).await;
+                let (processed, event_id) = match event {
+                    Ok(event) => {
+                        let event_id = event.event_id().map(ToString::to_string);
+                        // Data failing to be saved is retried on the next poll, never put aside
+                        (self.process_event(event).await, event_id)
+                    }
+                    Err(torii_ingester::Error::InvalidRow {
+                        name,
+                        event_id,
+                        data,
+                        reason,
+                    }) => (
+                        save_failed_event(
+                            &self.failed_event_repository,
+                            STREAM,
+                            &name,
+                            &event_id,
+                            &data,
+                            reason,
+                        )
+                        .await,
+                        Some(event_id),
+                    ),
+                    Err(err) => {
+                        error!("Error while fetching events: {}", err);
+                        status.error(format!("Error while fetching events: {err}"));
+                        (false, None)
+                    }
+                };
+
+                if !processed {
+                    if let Some(event_id) = &event_id {
+                        status.error(format!("Failed to process the event {event_id}"));
+                    }
+                    completed = false;
+                    // Keep the cursor before this event, so that it is retried on the next poll
+                    warn!("Stopping the processing of events until the next poll");
+                    break;
+                }
+
+                last_event_id = event_id.or(last_event_id);
                 event_count += 1;
             }
 
+            if let Some(last_event_id) = last_event_id {
+                if let Err(err) = self.save_cursor(last_event_id).await {
+                    error!("Failed to save the sync cursor: {}", err);
+                    status.error(format!("Failed to save the sync cursor: {err}"));
+                    completed = false;
+                }
+            }
+
+            if completed {
+                status.success();
+            }
+
             if event_count > 0 {
                 info!("Processed {} new events", event_count);
             } else {
                 debug!("No new events found");
             }
 
-            // Wait for 10 seconds before the next poll (or until stop signal)
+            // Wait for a live update or the polling interval (or until stop signal)
             select! {
-                () = tokio::time::sleep(std::time::Duration::from_secs(10)) => {
-                    debug!("Polling interval completed, checking for new events...");
+                () = live_updates.wait() => {
+                    debug!("Checking for new events...");
                 },
                 stop_result = &mut rx => {
                     match stop_result {
@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+use chaindata_models::models::FailedEventModel;
+use chaindata_repository::FailedEventRepository;
+use chrono::Utc;
+use serde_json::Value;
+use tracing::error;
+
+/// Puts torii data that could not be parsed in the dead-letter table, so that the sync can go on.
+///
+/// Only data that can never be parsed goes there: data that failed to be saved, maybe for a
+/// transient reason, is retried instead.
+///
+/// Returns `false` if the data could not be saved either, and the sync must stop to retry later.
+pub async fn save_failed_event(
+    repository: &FailedEventRepository,
+    stream: &'static str,
+    name: &str,
+    event_id: &str,
+    data: &Value,
+    error: impl Display,
+) -> bool {
+    error!("Failed to process {name} ({event_id}) from {stream}: {error}");
+
+    let result = repository
+        .save(FailedEventModel {
+            stream: stream.to_string(),
+            event_id: event_id.to_string(),
+            name: name.to_string(),
+            data: data.to_string(),
+            error: error.to_string(),
+            failed_at: Utc::now().naive_utc(),
+        })
+        .await;
+
+    match result {
+        Ok(inserted) => {
+            // Torii can return the same data multiple times, only count it once
+            if inserted {
+                metrics::counter!("torii_failed_events_total", "stream" => stream).increment(1);
+            }
+            true
+        }
+        Err(err) => {
+            error!("Failed to save the failed event {event_id}: {}", err);
+            false
+        }
+    }
+}
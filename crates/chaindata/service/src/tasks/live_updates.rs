@@ -0,0 +1,101 @@
+//! Live updates of torii, used as a wake-up signal for the SQL catch-up of the listener tasks.
+//!
+//! The streamed data is deliberately not saved. A gRPC message has no torii event id, so:
+//! - it cannot move the sync cursor, a restart would read it again or miss what came before it;
+//! - it cannot be deduplicated against the same row read by SQL, the primary key being the id;
+//! - it cannot be put in the dead-letter table when it fails to parse.
+//!
+//! Saving only what SQL returns keeps a single, ordered and resumable path to the database, at
+//! the cost of one SQL request per update.
+
+use std::{pin::Pin, time::Duration};
+
+use futures_util::FutureExt;
+use tokio_stream::{Stream, StreamExt};
+use torii_ingester::RawToriiData;
+use tracing::{debug, info, warn};
+
+/// Interval between two SQL catch-ups when no live update is received.
+///
+/// This is the polling interval when the gRPC subscription is down, and a safety net when it is up.
+pub const POLLING_INTERVAL: Duration = Duration::from_secs(10);
+
+type LiveStream<'a> = Pin<Box<dyn Stream<Item = RawToriiData> + Send + 'a>>;
+
+/// `LiveUpdates` wraps a gRPC subscription of torii, and is used to trigger a SQL catch-up as soon
+/// as torii indexes something new.
+///
+/// The data of the subscription itself is never used: it has no event id, so it cannot move the
+/// sync cursor. Instead, the subscription must be opened *before* the catch-up, so every update
+/// that happens during the catch-up stays buffered in the stream, and triggers another catch-up
+/// right after. As the catch-up reads from the sync cursor and duplicates are ignored by the
+/// database, nothing is lost or saved twice during this overlap window.
+///
+/// When the stream drops, the task falls back to polling until a new subscription succeeds.
+pub struct LiveUpdates<'a> {
+    enabled: bool,
+    stream: Option<LiveStream<'a>>,
+}
+
+impl<'a> LiveUpdates<'a> {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            stream: None,
+        }
+    }
+
+    /// Whether a new subscription must be opened before the next catch-up.
+    pub fn needs_subscription(&self) -> bool {
+        self.enabled && self.stream.is_none()
+    }
+
+    /// Stores the result of a subscription, falling back to polling if it failed.
+    pub fn subscribed<S>(&mut self, subscription: Result<S, torii_ingester::Error>)
+    where
+        S: Stream<Item = RawToriiData> + Send + 'a,
+    {
+        match subscription {
+            Ok(stream) => {
+                info!("Subscribed to torii live updates");
+                self.stream = Some(Box::pin(stream));
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to subscribe to torii, falling back to polling: {}",
+                    err
+                );
+            }
+        }
+    }
+
+    /// Waits for the next live update, or for the polling interval to elapse.
+    ///
+    /// All updates already buffered are consumed, so that a burst of updates triggers only one
+    /// catch-up.
+    pub async fn wait(&mut self) {
+        let Some(stream) = self.stream.as_mut() else {
+            tokio::time::sleep(POLLING_INTERVAL).await;
+            return;
+        };
+
+        match tokio::time::timeout(POLLING_INTERVAL, stream.next()).await {
+            Ok(Some(_)) => {
+                debug!("Received a live update from torii");
+                while let Some(next) = stream.next().now_or_never() {
+                    if next.is_none() {
+                        self.disconnected();
+                        return;
+                    }
+                }
+            }
+            Ok(None) => self.disconnected(),
+            Err(_) => debug!("No live update received during the polling interval"),
+        }
+    }
+
+    fn disconnected(&mut self) {
+        warn!("Torii live updates stream dropped, falling back to polling");
+        self.stream = None;
+    }
+}
@@ -1,29 +1,54 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    any::Any,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use tokio::sync::oneshot;
+use chrono::Utc;
+use status::{StatusHandle, TaskStatus};
+use tokio::{select, sync::oneshot};
+use tokio_stream::Stream;
+use torii_ingester::RawToriiData;
 
 use tracing::{debug, error, info};
 
 pub mod event_listener;
+pub mod failed_events;
+pub mod live_updates;
 pub mod model_listener;
+pub mod status;
+
+/// Stream of data fetched from torii with SQL, as used by the listener tasks.
+type ToriiStream = Pin<Box<dyn Stream<Item = Result<RawToriiData, torii_ingester::Error>> + Send>>;
 
 // TODO(Red): Migrate this to a dedicated crate, as we could add more informations later.
 
 /// A task is an utility trait that is used to factorize some of the work required for
 /// each indexer subsystem, as they are a task that is handled by tokio, and must be kept alive.
+///
+/// The task must report its progress with the given [`StatusHandle`], and is restarted by its
+/// [`TaskWrapper`] if it panics.
 #[async_trait::async_trait]
 pub trait Task: Sized + Send + Sync + 'static {
     const NAME: &'static str;
 
-    async fn do_task(self: Arc<Self>, stop_channel: oneshot::Receiver<()>);
+    async fn do_task(self: Arc<Self>, stop_channel: oneshot::Receiver<()>, status: StatusHandle);
 
     fn wrap(self) -> TaskWrapper<Self> {
         TaskWrapper::new(self)
     }
 }
 
+/// Delay before the first restart of a crashed task, doubled on each consecutive crash.
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay between two restarts of a crashed task.
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(60);
+
 pub struct TaskWrapper<T: Task> {
     stop_handle: Mutex<Option<oneshot::Sender<()>>>,
+    status: StatusHandle,
     task: Arc<T>,
 }
 
@@ -31,10 +56,16 @@ impl<T: Task + 'static> TaskWrapper<T> {
     pub fn new(task: T) -> Self {
         Self {
             stop_handle: Mutex::new(None),
+            status: StatusHandle::new(T::NAME),
             task: Arc::new(task),
         }
     }
 
+    /// Returns the current status of the task.
+    pub fn status(&self) -> TaskStatus {
+        self.status.get()
+    }
+
     pub fn start(&self) {
         let (tx, rx) = oneshot::channel();
 
@@ -49,11 +80,70 @@ impl<T: Task + 'static> TaskWrapper<T> {
             info!("Failed to acquire lock for starting {}", T::NAME);
             return;
         }
-        let task = self.task.clone();
 
-        tokio::spawn(async {
-            T::do_task(task, rx).await;
-        });
+        tokio::spawn(Self::supervise(self.task.clone(), self.status.clone(), rx));
+    }
+
+    /// Runs the task until it is stopped, restarting it with an exponential backoff if it crashes.
+    async fn supervise(task: Arc<T>, status: StatusHandle, mut stop_rx: oneshot::Receiver<()>) {
+        let mut consecutive_crashes = 0;
+
+        loop {
+            let started_at = Utc::now();
+            let (task_stop_tx, task_stop_rx) = oneshot::channel();
+            let mut handle = tokio::spawn(T::do_task(task.clone(), task_stop_rx, status.clone()));
+            status.update(|status| status.running = true);
+
+            let result = select! {
+                result = &mut handle => result,
+                _ = &mut stop_rx => {
+                    // Forward the stop signal, and wait for the task to finish
+                    let _ = task_stop_tx.send(());
+                    let _ = handle.await;
+                    status.update(|status| status.running = false);
+                    return;
+                }
+            };
+
+            status.update(|status| status.running = false);
+
+            let error = match result {
+                Ok(()) => {
+                    info!("{} finished", T::NAME);
+                    return;
+                }
+                Err(err) if err.is_panic() => {
+                    format!("panicked: {}", panic_message(err.into_panic().as_ref()))
+                }
+                Err(err) => err.to_string(),
+            };
+            error!("{} crashed: {}", T::NAME, error);
+
+            // Only back off if the task did not make any progress since its last start
+            if status
+                .get()
+                .last_success
+                .is_some_and(|last_success| last_success > started_at)
+            {
+                consecutive_crashes = 0;
+            }
+            let delay = RESTART_BASE_DELAY
+                .saturating_mul(2u32.saturating_pow(consecutive_crashes))
+                .min(RESTART_MAX_DELAY);
+            consecutive_crashes += 1;
+
+            status.update(|status| {
+                status.restarts += 1;
+                status.last_error = Some(error);
+                status.last_error_at = Some(Utc::now());
+            });
+
+            info!("Restarting {} in {:?}", T::NAME, delay);
+            select! {
+                () = tokio::time::sleep(delay) => {},
+                _ = &mut stop_rx => return,
+            }
+        }
     }
 
     pub fn stop(&self) {
@@ -72,3 +162,12 @@ impl<T: Task + 'static> TaskWrapper<T> {
         }
     }
 }
+
+/// Extracts the message of a panic, if it has one.
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
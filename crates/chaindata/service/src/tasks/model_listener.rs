@@ -2,18 +2,31 @@ use std::{cmp::max, sync::Arc};
 
 use chaindata_models::{
     events::EventId,
-    models::{LandModel, LandStakeModel},
+    models::{AuctionModel, LandModel, LandStakeModel, SyncCursorModel},
 };
-use chaindata_repository::{LandRepository, LandStakeRepository};
-use chrono::{DateTime, Utc};
+use chaindata_repository::{
+    AuctionRepository, FailedEventRepository, LandRepository, LandStakeRepository,
+    SyncCursorRepository,
+};
+use chrono::Utc;
 use ponziland_models::models::Model;
 use sqlx::error::DatabaseError;
 use tokio::select;
 use tokio_stream::StreamExt;
 use torii_ingester::{RawToriiData, ToriiClient};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+use crate::error::Error;
+
+use super::{
+    failed_events::save_failed_event,
+    live_updates::{LiveUpdates, POLLING_INTERVAL},
+    status::StatusHandle,
+    Task, ToriiStream,
+};
 
-use super::Task;
+/// Name of the stream in the `sync_cursor` table.
+const STREAM: &str = "models";
 
 /// `ModelsListenerTask` is a task that subscribes to some models of the on-chain indexer (torii),
 /// and pushes them to the local database.
@@ -21,11 +34,19 @@ use super::Task;
 /// Supported models:
 /// - Land
 /// - `LandStake`
-/// - Auctions (soon, TODO)
+/// - Auction
+///
+/// The last processed torii event id is persisted as a sync cursor, so that the task resumes
+/// exactly where it stopped. If live updates are enabled, the gRPC subscription of torii is used
+/// to catch up as soon as something new is indexed (see [`LiveUpdates`]).
 pub struct ModelListenerTask {
     client: Arc<ToriiClient>,
     land_repository: Arc<LandRepository>,
     land_stake_repository: Arc<LandStakeRepository>,
+    auction_repository: Arc<AuctionRepository>,
+    sync_cursor_repository: Arc<SyncCursorRepository>,
+    failed_event_repository: Arc<FailedEventRepository>,
+    live_updates: bool,
 }
 
 impl ModelListenerTask {
@@ -33,75 +54,146 @@ impl ModelListenerTask {
         client: Arc<ToriiClient>,
         land_repository: Arc<LandRepository>,
         land_stake_repository: Arc<LandStakeRepository>,
+        auction_repository: Arc<AuctionRepository>,
+        sync_cursor_repository: Arc<SyncCursorRepository>,
+        failed_event_repository: Arc<FailedEventRepository>,
+        live_updates: bool,
     ) -> Self {
         Self {
             client,
             land_repository,
             land_stake_repository,
+            auction_repository,
+            sync_cursor_repository,
+            failed_event_repository,
+            live_updates,
         }
     }
 
-    /// Gets the most recent update time across all model tables.
+    /// Gets the id of the most recent update across all model tables.
     /// This is used to determine where to start when catching up with model updates.
-    async fn get_last_update_time(&self) -> Result<DateTime<Utc>, sqlx::Error> {
+    async fn get_last_event_id(&self) -> Result<Option<EventId>, sqlx::Error> {
+        let land_latest = self.land_repository.get_latest_event_id().await?;
+        let land_stake_latest = self.land_stake_repository.get_latest_event_id().await?;
+        let auction_latest = self.auction_repository.get_latest_event_id().await?;
+
+        Ok(max(max(land_latest, land_stake_latest), auction_latest))
+    }
+
+    /// Opens the stream of models to process, starting from the sync cursor if there is one.
+    async fn models_stream(&self) -> Result<ToriiStream, Error> {
+        if let Some(cursor) = self.sync_cursor_repository.get(STREAM).await? {
+            info!("Polling for models from cursor: {}", cursor.last_event_id);
+
+            return Ok(Box::pin(
+                self.client
+                    .get_all_entities_from_cursor(&cursor.last_event_id)?,
+            ));
+        }
+
+        // No cursor yet, start after the last model update that we know of
+        if let Some(last_event_id) = self.get_last_event_id().await? {
+            let last_event_id = last_event_id.to_torii_string();
+            info!(
+                "No sync cursor, polling for models after: {}",
+                last_event_id
+            );
+
+            return Ok(Box::pin(
+                self.client.get_all_entities_from_cursor(&last_event_id)?,
+            ));
+        }
+
         // If we did not start indexing, start from the beginning
-        let fallback_time = DateTime::UNIX_EPOCH.naive_utc();
-
-        // Get latest from land table
-        let land_latest = self
-            .land_repository
-            .get_latest_timestamp()
-            .await?
-            .unwrap_or(fallback_time);
-        let land_stake_latest = self
-            .land_stake_repository
-            .get_latest_timestamp()
-            .await?
-            .unwrap_or(fallback_time);
-
-        Ok(max(land_latest, land_stake_latest).and_utc())
+        info!("No sync cursor nor models, polling for all models");
+
+        Ok(Box::pin(self.client.get_all_entities()?))
     }
 
-    #[allow(clippy::match_wildcard_for_single_variants)]
-    async fn process_model(&self, model_data: RawToriiData) {
-        let model = Model::parse(model_data).expect("Error while parsing model data");
-        let result = match model.model {
+    /// Processes a model, returning `false` if it could not be saved and must be retried.
+    ///
+    /// Models that cannot be parsed are saved as failed events instead.
+    async fn process_model(&self, model_data: RawToriiData) -> bool {
+        let RawToriiData::Json {
+            name,
+            data,
+            at,
+            event_id,
+        } = model_data
+        else {
+            // Without an event id, a model cannot be saved
+            debug!("Ignoring GRPC model");
+            return true;
+        };
+
+        let parsed = EventId::parse_from_torii(&event_id)
+            .map_err(|err| err.to_string())
+            .and_then(|id| {
+                Model::from_json(&name, data.clone())
+                    .map(|model| (id, model))
+                    .map_err(|err| err.to_string())
+            });
+
+        let (id, model) = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                return save_failed_event(
+                    &self.failed_event_repository,
+                    STREAM,
+                    &name,
+                    &event_id,
+                    &data,
+                    err,
+                )
+                .await;
+            }
+        };
+
+        let at = at.naive_utc();
+        let result = match model {
             Model::Land(land) => {
                 self.land_repository
-                    .save(LandModel::from_at(
-                        &land,
-                        EventId::parse_from_torii(&model.event_id.unwrap()).unwrap(),
-                        model.timestamp.unwrap_or(Utc::now()).naive_utc(),
-                    ))
+                    .save(LandModel::from_at(&land, id, at))
                     .await
             }
             Model::LandStake(land_stake) => {
                 self.land_stake_repository
-                    .save(LandStakeModel::from_at(
-                        &land_stake,
-                        EventId::parse_from_torii(&model.event_id.unwrap()).unwrap(),
-                        model.timestamp.unwrap_or(Utc::now()).naive_utc(),
-                    ))
+                    .save(LandStakeModel::from_at(&land_stake, id, at))
                     .await
             }
-            _ => {
-                //TODO: Implement this later
-                return;
+            Model::Auction(auction) => {
+                self.auction_repository
+                    .save(AuctionModel::from_at(&auction, id, at))
+                    .await
             }
         };
 
-        if let Err(chaindata_repository::Error::SqlError(err)) = result {
-            if !err
-                .as_database_error()
-                .is_some_and(DatabaseError::is_unique_violation)
+        match result {
+            Ok(_) => info!("Successfully saved event!"),
+            Err(chaindata_repository::Error::SqlError(err))
+                if err
+                    .as_database_error()
+                    .is_some_and(DatabaseError::is_unique_violation) =>
             {
+                // It is a duplicate, so ignore it
+            }
+            Err(err) => {
                 error!("Failed to save event: {}", err);
+                return false;
             }
-
-            // It is a duplicate, so ignore it
-            return;
         }
-        info!("Successfully saved event!");
+
+        true
+    }
+
+    async fn save_cursor(&self, last_event_id: String) -> Result<(), chaindata_repository::Error> {
+        self.sync_cursor_repository
+            .save(SyncCursorModel {
+                stream: STREAM.to_string(),
+                last_event_id,
+                updated_at: Utc::now().naive_utc(),
+            })
+            .await
     }
 }
 
@@ -109,43 +201,107 @@ impl ModelListenerTask {
 impl Task for ModelListenerTask {
     const NAME: &'static str = "ModelsListenerTask";
 
-    async fn do_task(self: std::sync::Arc<Self>, mut rx: tokio::sync::oneshot::Receiver<()>) {
-        info!("Starting ModelListenerTask with 10-second polling interval");
+    async fn do_task(
+        self: std::sync::Arc<Self>,
+        mut rx: tokio::sync::oneshot::Receiver<()>,
+        status: StatusHandle,
+    ) {
+        info!(
+            "Starting ModelListenerTask (live updates: {}, polling interval: {:?})",
+            self.live_updates, POLLING_INTERVAL
+        );
 
-        loop {
-            // Poll for new models from the database
-            let last_check = self
-                .get_last_update_time()
-                .await
-                .expect("Failed to retrieve last update time");
+        let mut live_updates = LiveUpdates::new(self.live_updates);
 
-            let last_check = last_check - chrono::Duration::seconds(1);
+        loop {
+            // Subscribe before the catch-up, so that no update is missed in between
+            if live_updates.needs_subscription() {
+                live_updates.subscribed(self.client.subscribe_entities().await);
+            }
 
-            info!("Polling for models after: {:?}", last_check);
+            // Whether the whole catch-up succeeded
+            let mut completed = true;
 
-            // Get all entities that were updated after the last check
-            let mut models_stream = self
-                .client
-                .get_all_entities_after(last_check)
-                .expect("Error while fetching entities");
+            let mut models_stream = match self.models_stream().await {
+                Ok(models_stream) => models_stream,
+                Err(err) => {
+                    error!("Failed to open the models stream: {}", err);
+                    status.error(format!("Failed to open the models stream: {err}"));
+                    completed = false;
+                    Box::pin(tokio_stream::empty())
+                }
+            };
 
-            // Process models as they go
+            // Process models as they go, they are ordered by event id
             let mut model_count = 0;
+            let mut last_event_id = None;
             while let Some(model) = models_stream.next().await {
-                self.process_model(model).await;
+                let (processed, event_id) = match model {
+                    Ok(model) => {
+                        let event_id = model.event_id().map(ToString::to_string);
+                        // Data failing to be saved is retried on the next poll, never put aside
+                        (self.process_model(model).await, event_id)
+                    }
+                    Err(torii_ingester::Error::InvalidRow {
+                        name,
+                        event_id,
+                        data,
+                        reason,
+                    }) => (
+                        save_failed_event(
+                            &self.failed_event_repository,
+                            STREAM,
+                            &name,
+                            &event_id,
+                            &data,
+                            reason,
+                        )
+                        .await,
+                        Some(event_id),
+                    ),
+                    Err(err) => {
+                        error!("Error while fetching models: {}", err);
+                        status.error(format!("Error while fetching models: {err}"));
+                        (false, None)
+                    }
+                };
+
+                if !processed {
+                    if let Some(event_id) = &event_id {
+                        status.error(format!("Failed to process the model {event_id}"));
+                    }
+                    completed = false;
+                    // Keep the cursor before this model, so that it is retried on the next poll
+                    warn!("Stopping the processing of models until the next poll");
+                    break;
+                }
+
+                last_event_id = event_id.or(last_event_id);
                 model_count += 1;
             }
 
+            if let Some(last_event_id) = last_event_id {
+                if let Err(err) = self.save_cursor(last_event_id).await {
+                    error!("Failed to save the sync cursor: {}", err);
+                    status.error(format!("Failed to save the sync cursor: {err}"));
+                    completed = false;
+                }
+            }
+
+            if completed {
+                status.success();
+            }
+
             if model_count > 0 {
                 info!("Processed {} new models", model_count);
             } else {
                 debug!("No new models found");
             }
 
-            // Wait for 10 seconds before the next poll (or until stop signal)
+            // Wait for a live update or the polling interval (or until stop signal)
             select! {
-                () = tokio::time::sleep(std::time::Duration::from_secs(10)) => {
-                    debug!("Polling interval completed, checking for new models...");
+                () = live_updates.wait() => {
+                    debug!("Checking for new models...");
                 },
                 stop_result = &mut rx => {
                     match stop_result {
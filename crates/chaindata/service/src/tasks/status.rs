@@ -0,0 +1,132 @@
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tracing::error;
+
+/// Status of a supervised task, as seen from the outside.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub name: &'static str,
+    /// Whether the task is currently running (false when stopped, or waiting to be restarted).
+    pub running: bool,
+    /// Number of times the task was restarted after a crash.
+    pub restarts: u32,
+    /// Last time the task completed a unit of work successfully.
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+impl TaskStatus {
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            running: false,
+            restarts: 0,
+            last_success: None,
+            last_error: None,
+            last_error_at: None,
+        }
+    }
+
+    /// Whether the task is running and had a success recently enough.
+    #[must_use]
+    pub fn is_healthy(&self, max_age: Duration, now: DateTime<Utc>) -> bool {
+        self.running
+            && self
+                .last_success
+                .is_some_and(|last_success| now - last_success <= max_age)
+    }
+}
+
+/// Handle given to a task to report its progress to its supervisor.
+#[derive(Clone)]
+pub struct StatusHandle(Arc<Mutex<TaskStatus>>);
+
+impl StatusHandle {
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        Self(Arc::new(Mutex::new(TaskStatus::new(name))))
+    }
+
+    /// Records a successful unit of work.
+    pub fn success(&self) {
+        self.update(|status| status.last_success = Some(Utc::now()));
+    }
+
+    /// Records an error of the task, that did not stop it.
+    pub fn error(&self, error: impl Display) {
+        let error = error.to_string();
+        self.update(|status| {
+            status.last_error = Some(error);
+            status.last_error_at = Some(Utc::now());
+        });
+    }
+
+    /// Returns a snapshot of the current status.
+    #[must_use]
+    pub fn get(&self) -> TaskStatus {
+        match self.0.lock() {
+            Ok(status) => status.clone(),
+            // A panic while holding the lock cannot leave the status in an invalid state
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub(crate) fn update(&self, update: impl FnOnce(&mut TaskStatus)) {
+        match self.0.lock() {
+            Ok(mut status) => update(&mut status),
+            Err(poisoned) => {
+                error!("Task status lock was poisoned, recovering it");
+                update(&mut poisoned.into_inner());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_healthy() {
+        let now = Utc::now();
+        let max_age = Duration::minutes(5);
+        let mut status = TaskStatus::new("Test");
+
+        // Never succeeded
+        status.running = true;
+        assert!(!status.is_healthy(max_age, now));
+
+        status.last_success = Some(now - Duration::minutes(1));
+        assert!(status.is_healthy(max_age, now));
+
+        // Stuck for too long
+        status.last_success = Some(now - Duration::minutes(6));
+        assert!(!status.is_healthy(max_age, now));
+
+        // Not running anymore
+        status.last_success = Some(now);
+        status.running = false;
+        assert!(!status.is_healthy(max_age, now));
+    }
+
+    #[test]
+    fn test_status_handle() {
+        let handle = StatusHandle::new("Test");
+        assert!(handle.get().last_success.is_none());
+
+        handle.error("Oops");
+        handle.success();
+
+        let status = handle.get();
+        assert_eq!(status.name, "Test");
+        assert!(status.last_success.is_some());
+        assert_eq!(status.last_error.as_deref(), Some("Oops"));
+    }
+}
@@ -1,6 +1,6 @@
 use ekubo::EkuboClient;
 use starknet::{
-    core::types::Felt,
+    core::types::{BlockId, BlockTag, Felt},
     providers::{jsonrpc::HttpTransport, JsonRpcClient, Url},
 };
 
@@ -48,7 +48,10 @@ pub async fn main() {
 
     println!("Using most popular pool: {}", &popular.key);
 
-    let price = client.read_pool_price(&popular.key).await.unwrap();
+    let price = client
+        .read_pool_price(&popular.key, BlockId::Tag(BlockTag::Latest))
+        .await
+        .unwrap();
 
     println!("Pair ratio: 1 ePAPER = {price} eSTRK");
     println!("Pair ratio: 1 eSTRK = {} ePAPER", price.inverse());
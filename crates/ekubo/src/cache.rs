@@ -0,0 +1,158 @@
+//! Cache of the pools read at a block, so routing and quoting do not read a pool twice.
+
+use std::collections::HashMap;
+
+use starknet::core::types::Felt;
+
+use crate::{
+    api::pool::Pool,
+    contract::{
+        pool_price::{LiquidityResponse, PoolKey},
+        pool_state::PoolState,
+    },
+};
+
+/// Pools read at a single block.
+///
+/// Entries are only kept while the block does not change, nothing is cached before the first
+/// block is set.
+#[derive(Debug, Default)]
+pub struct PoolCache {
+    block_number: Option<u64>,
+    prices: HashMap<PoolKey, LiquidityResponse>,
+    states: HashMap<PoolKey, PoolState>,
+    pools: HashMap<(Felt, Felt), Vec<Pool>>,
+}
+
+impl PoolCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The block the entries were read at.
+    #[must_use]
+    pub fn block_number(&self) -> Option<u64> {
+        self.block_number
+    }
+
+    /// Moves the cache to a block, dropping every entry when it is a different block.
+    pub fn set_block(&mut self, block_number: u64) {
+        if self.block_number != Some(block_number) {
+            self.block_number = Some(block_number);
+            self.clear();
+        }
+    }
+
+    /// Drops every entry, until they are read again.
+    pub fn clear(&mut self) {
+        self.prices.clear();
+        self.states.clear();
+        self.pools.clear();
+    }
+
+    #[must_use]
+    pub fn price(&self, key: &PoolKey) -> Option<&LiquidityResponse> {
+        self.prices.get(key)
+    }
+
+    pub fn insert_price(&mut self, key: PoolKey, price: LiquidityResponse) {
+        if self.block_number.is_some() {
+            self.prices.insert(key, price);
+        }
+    }
+
+    /// The state of a pool, which also gives its price.
+    #[must_use]
+    pub fn state(&self, key: &PoolKey) -> Option<&PoolState> {
+        self.states.get(key)
+    }
+
+    pub fn insert_state(&mut self, key: PoolKey, state: PoolState) {
+        if self.block_number.is_some() {
+            self.prices.insert(
+                key.clone(),
+                LiquidityResponse {
+                    sqrt_ratio: state.sqrt_ratio,
+                    tick: state.tick,
+                },
+            );
+            self.states.insert(key, state);
+        }
+    }
+
+    /// The pools of a pair of tokens, in any order.
+    #[must_use]
+    pub fn pools(&self, token0: Felt, token1: Felt) -> Option<&Vec<Pool>> {
+        self.pools.get(&ordered(token0, token1))
+    }
+
+    pub fn insert_pools(&mut self, token0: Felt, token1: Felt, pools: Vec<Pool>) {
+        if self.block_number.is_some() {
+            self.pools.insert(ordered(token0, token1), pools);
+        }
+    }
+}
+
+fn ordered(token0: Felt, token1: Felt) -> (Felt, Felt) {
+    (token0.min(token1), token0.max(token1))
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::U256;
+
+    use super::*;
+    use crate::contract::pool_price::I129;
+
+    fn key(fee: u128) -> PoolKey {
+        PoolKey {
+            token0: Felt::ONE,
+            token1: Felt::TWO,
+            fee,
+            tick_spacing: 1,
+            extension: Felt::ZERO,
+        }
+    }
+
+    fn state(liquidity: u128) -> PoolState {
+        PoolState {
+            sqrt_ratio: U256::from_words(0, 1),
+            tick: I129 {
+                value: 0,
+                sign: false,
+            },
+            liquidity,
+        }
+    }
+
+    #[test]
+    fn test_block_invalidation() {
+        let mut cache = PoolCache::new();
+
+        // Nothing is cached without a block
+        cache.insert_state(key(0), state(10));
+        assert!(cache.state(&key(0)).is_none());
+
+        cache.set_block(100);
+        cache.insert_state(key(0), state(10));
+        cache.insert_pools(Felt::TWO, Felt::ONE, Vec::new());
+        assert_eq!(cache.state(&key(0)), Some(&state(10)));
+        assert_eq!(
+            cache.price(&key(0)).map(|price| price.sqrt_ratio),
+            Some(state(10).sqrt_ratio)
+        );
+        assert!(cache.state(&key(1)).is_none());
+        assert!(cache.pools(Felt::ONE, Felt::TWO).is_some());
+
+        // The same block keeps the entries
+        cache.set_block(100);
+        assert!(cache.state(&key(0)).is_some());
+
+        cache.set_block(101);
+        assert_eq!(cache.block_number(), Some(101));
+        assert!(cache.state(&key(0)).is_none());
+        assert!(cache.price(&key(0)).is_none());
+        assert!(cache.pools(Felt::ONE, Felt::TWO).is_none());
+    }
+}
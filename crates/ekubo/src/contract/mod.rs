@@ -1,7 +1,14 @@
-use starknet::core::codec::Error as CodecError;
+use starknet::{
+    core::{
+        codec::Error as CodecError,
+        types::{requests::CallRequest, BlockId, Felt, FunctionCall},
+    },
+    providers::{Provider, ProviderRequestData, ProviderResponseData},
+};
 use thiserror::Error;
 
 pub mod pool_price;
+pub mod pool_state;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -10,3 +17,43 @@ pub enum Error {
     #[error("An error occurred while interacting with the RPC: {0}")]
     RpcError(String),
 }
+
+/// Largest number of calls sent in one request, nodes limit the size of batches.
+const MAX_BATCH_SIZE: usize = 100;
+
+/// Calls view functions at the same block, in as few JSON-RPC batches as possible.
+///
+/// # Errors
+/// Returns an error if one of the calls fails.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(calls)))]
+pub async fn batch_call<T: Provider + Send + Sync + std::fmt::Debug>(
+    rpc_client: T,
+    calls: Vec<FunctionCall>,
+    block_id: BlockId,
+) -> Result<Vec<Vec<Felt>>, Error> {
+    let mut results = Vec::with_capacity(calls.len());
+    for chunk in calls.chunks(MAX_BATCH_SIZE) {
+        let requests = chunk
+            .iter()
+            .map(|call| {
+                ProviderRequestData::Call(CallRequest {
+                    request: call.clone(),
+                    block_id,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let responses = rpc_client
+            .batch_requests(requests)
+            .await
+            .map_err(|e| Error::RpcError(e.to_string()))?;
+        for response in responses {
+            let ProviderResponseData::Call(result) = response else {
+                return Err(Error::RpcError("Unexpected response to a call".to_string()));
+            };
+            results.push(result);
+        }
+    }
+
+    Ok(results)
+}
@@ -1,15 +1,15 @@
-use super::Error;
+use super::{batch_call, Error};
 use serde::{Deserialize, Serialize};
 use starknet::{
     core::{
         codec::{Decode, Encode},
-        types::{BlockId, BlockTag, Felt, FunctionCall, U256},
+        types::{BlockId, Felt, FunctionCall, U256},
     },
     macros::selector,
 };
 use std::fmt::Display;
 
-#[derive(Clone, PartialEq, Debug, Decode, Encode, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Decode, Encode, Serialize, Deserialize)]
 pub struct PoolKey {
     pub token0: Felt,
     pub token1: Felt,
@@ -40,7 +40,24 @@ pub struct LiquidityResponse {
     pub tick: I129,
 }
 
-/// Read the pool price from the given contract address and pool key.
+/// Builds a call to a function of the core contract taking a pool key.
+pub(crate) fn pool_call(
+    contract_address: Felt,
+    entry_point_selector: Felt,
+    pool: &PoolKey,
+) -> Result<FunctionCall, Error> {
+    let mut calldata = Vec::new();
+    pool.encode(&mut calldata)
+        .map_err(|_| Error::RpcError("Impossible to encode the pool data".to_string()))?;
+
+    Ok(FunctionCall {
+        contract_address,
+        entry_point_selector,
+        calldata,
+    })
+}
+
+/// Read the pool price from the given contract address and pool key, at the given block.
 ///
 /// # Errors
 /// Returns an error if the RPC call fails or if the response cannot be decoded.
@@ -49,19 +66,12 @@ pub async fn read_pool_price<T: starknet::providers::Provider + Send + Sync + st
     rpc_client: T,
     contract_address: Felt,
     pool: &PoolKey,
+    block_id: BlockId,
 ) -> Result<LiquidityResponse, Error> {
-    let mut call_data = Vec::new();
-    pool.encode(&mut call_data)
-        .map_err(|_| Error::RpcError("Impossible to encode the pool data".to_string()))?;
-
     let response = rpc_client
         .call(
-            FunctionCall {
-                contract_address,
-                entry_point_selector: selector!("get_pool_price"),
-                calldata: call_data,
-            },
-            BlockId::Tag(BlockTag::Latest),
+            pool_call(contract_address, selector!("get_pool_price"), pool)?,
+            block_id,
         )
         .await
         .map_err(|e| Error::RpcError(e.to_string()))?;
@@ -70,3 +80,26 @@ pub async fn read_pool_price<T: starknet::providers::Provider + Send + Sync + st
         response.iter().collect::<Vec<&Felt>>(),
     )?)
 }
+
+/// Read the prices of many pools in JSON-RPC batches, all at the given block.
+///
+/// # Errors
+/// Returns an error if one of the RPC calls fails or if a response cannot be decoded.
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+pub async fn read_pool_prices<T: starknet::providers::Provider + Send + Sync + std::fmt::Debug>(
+    rpc_client: T,
+    contract_address: Felt,
+    pools: &[PoolKey],
+    block_id: BlockId,
+) -> Result<Vec<LiquidityResponse>, Error> {
+    let calls = pools
+        .iter()
+        .map(|pool| pool_call(contract_address, selector!("get_pool_price"), pool))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    batch_call(rpc_client, calls, block_id)
+        .await?
+        .iter()
+        .map(|response| Ok(LiquidityResponse::decode(response)?))
+        .collect()
+}
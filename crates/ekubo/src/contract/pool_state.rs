@@ -0,0 +1,51 @@
+use super::{
+    batch_call,
+    pool_price::{pool_call, LiquidityResponse, PoolKey, I129},
+    Error,
+};
+use starknet::{
+    core::{
+        codec::Decode,
+        types::{BlockId, Felt, U256},
+    },
+    macros::selector,
+};
+
+/// State of a pool at the current price.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PoolState {
+    /// Square root of the price, a 64.128 fixed point number.
+    pub sqrt_ratio: U256,
+    pub tick: I129,
+    /// Liquidity of the current tick range.
+    pub liquidity: u128,
+}
+
+/// Read the price and the active liquidity of a pool in a single batch, at the given block.
+///
+/// # Errors
+/// Returns an error if one of the RPC calls fails or if a response cannot be decoded.
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+pub async fn read_pool_state<T: starknet::providers::Provider + Send + Sync + std::fmt::Debug>(
+    rpc_client: T,
+    contract_address: Felt,
+    pool: &PoolKey,
+    block_id: BlockId,
+) -> Result<PoolState, Error> {
+    let calls = vec![
+        pool_call(contract_address, selector!("get_pool_liquidity"), pool)?,
+        pool_call(contract_address, selector!("get_pool_price"), pool)?,
+    ];
+    let [liquidity, price] =
+        <[Vec<Felt>; 2]>::try_from(batch_call(rpc_client, calls, block_id).await?)
+            .map_err(|_| Error::RpcError("Unexpected number of responses".to_string()))?;
+
+    let liquidity = u128::decode(&liquidity)?;
+    let price = LiquidityResponse::decode(&price)?;
+
+    Ok(PoolState {
+        sqrt_ratio: price.sqrt_ratio,
+        tick: price.tick,
+        liquidity,
+    })
+}
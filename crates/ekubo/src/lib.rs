@@ -1,23 +1,46 @@
 use crate::api::pool::Pool;
 use crate::contract::pool_price::PoolKey;
 use api::pool::get_all_pools;
-use contract::pool_price::read_pool_price;
+use cache::PoolCache;
+use contract::pool_price::{read_pool_prices, LiquidityResponse};
+use contract::pool_state::{read_pool_state, PoolState};
+use futures_util::{stream, StreamExt};
 use math::u256fd128::U256FD128;
 use price::PairRatio;
+use quote::Quote;
 use reqwest::Client as ReqwestClient;
+use route::{candidate_paths, Hop, Route};
+use score::ScoringStrategy;
+use starknet::core::types::BlockId;
 pub use starknet::core::types::Felt;
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
 use thiserror::Error;
 
 pub mod api;
+pub mod cache;
 pub mod contract;
 pub mod math;
 pub mod price;
+pub mod quote;
+pub mod route;
+pub mod score;
+pub mod twap;
+
+#[cfg(test)]
+mod testing;
+
+/// Number of requests sent to the Ekubo API at the same time.
+const CONCURRENT_API_REQUESTS: usize = 8;
 
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Pool not found")]
     PoolNotFound,
+    #[error("Swap cannot be quoted")]
+    InvalidSwap,
     #[error(transparent)]
     ApiError(#[from] api::Error),
     #[error("RPC error")]
@@ -35,6 +58,7 @@ where
     // Allows to pass a reqwest client if needed
     http_client: Arc<ReqwestClient>,
     ekubo_api: String,
+    cache: Mutex<PoolCache>,
 }
 
 impl<Client> EkuboClient<Client>
@@ -47,39 +71,537 @@ where
             rpc_client,
             http_client: Arc::new(ReqwestClient::new()),
             ekubo_api,
+            cache: Mutex::new(PoolCache::new()),
         }
     }
 
+    fn cache(&self) -> MutexGuard<'_, PoolCache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The cache, if its entries were read at `block_id`.
+    ///
+    /// Reads at another block, or at a tag, are neither served from nor kept in the cache.
+    fn cache_at(&self, block_id: BlockId) -> Option<MutexGuard<'_, PoolCache>> {
+        let cache = self.cache();
+        let matches =
+            matches!(block_id, BlockId::Number(number) if cache.block_number() == Some(number));
+        matches.then_some(cache)
+    }
+
+    /// Reads the latest block number, and drops the cached pools when it changed.
+    ///
+    /// Reads at that block are then cached, until the next block is synced. Callers pass the
+    /// returned block to every read of a snapshot, so that it stays at one block even when
+    /// another caller syncs a newer one meanwhile.
+    ///
+    /// # Errors
+    /// Returns an error if the RPC call fails, the cache then stays at its block
+    pub async fn sync_block(&self) -> Result<u64, Error> {
+        let block_number = self
+            .rpc_client
+            .block_number()
+            .await
+            .map_err(|err| contract::Error::RpcError(err.to_string()))?;
+        self.cache().set_block(block_number);
+        Ok(block_number)
+    }
+
     /// Get all pools for a given token pair.
     ///
     /// # Errors
     /// Returns an error if the request to the Ekubo API fails
     pub async fn get_pools(&self, token0: Felt, token1: Felt) -> Result<Vec<Pool>, Error> {
-        Ok(get_all_pools(
+        let cached = self.cache().pools(token0, token1).cloned();
+        if let Some(pools) = cached {
+            return Ok(pools);
+        }
+
+        let pools = get_all_pools(
             &self.http_client,
             &self.ekubo_api,
             &token0.to_fixed_hex_string(),
             &token1.to_fixed_hex_string(),
         )
-        .await?)
+        .await?;
+        self.cache().insert_pools(token0, token1, pools.clone());
+        Ok(pools)
+    }
+
+    async fn pool_prices(
+        &self,
+        pools: &[PoolKey],
+        block_id: BlockId,
+    ) -> Result<Vec<LiquidityResponse>, Error> {
+        let cached = match self.cache_at(block_id) {
+            Some(cache) => pools
+                .iter()
+                .map(|pool| cache.price(pool).cloned())
+                .collect::<Vec<_>>(),
+            None => vec![None; pools.len()],
+        };
+        let missing = pools
+            .iter()
+            .zip(&cached)
+            .filter(|(_, price)| price.is_none())
+            .map(|(pool, _)| pool.clone())
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(cached.into_iter().flatten().collect());
+        }
+
+        let read =
+            read_pool_prices(&self.rpc_client, self.contract_address, &missing, block_id).await?;
+        if let Some(mut cache) = self.cache_at(block_id) {
+            for (pool, price) in missing.into_iter().zip(&read) {
+                cache.insert_price(pool, price.clone());
+            }
+        }
+
+        let mut read = read.into_iter();
+        Ok(cached
+            .into_iter()
+            .map(|price| price.or_else(|| read.next()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                contract::Error::RpcError("Missing prices in the response".to_string())
+            })?)
     }
 
-    /// Reads the price of a pool.
+    /// Reads the price of a pool at a block.
     ///
     /// # Errors
-    /// Returns an error if the request to the Ekubo API fails
-    pub async fn read_pool_price(&self, pool: &PoolKey) -> Result<PairRatio, Error> {
-        let response = read_pool_price(&self.rpc_client, self.contract_address, pool).await?;
-        // Then do the conversion
-        // Let's compute the price from this result.
-        // The value sqrt_ratio is a 64.128 fixed point number.
-        // To convert it to a price, first divide it by 2**128, then square it to get the price.
-        // Since USDC is token1, this value is the price of the pool in USDC/ETH.
-        // (0x029895c9cbfca44f2c46e6e9b5459b / 2**128)**2 == 1.56914... ×10^-9.
-        // To adjust for display, we have to account for the decimal difference between the USDC and ETH tokens.
-        // Because USDC has 6 decimals and ETH has 18 decimals, we need to scale it up by 10**(18-6) to be human readable.
-        // 1.56914e-9 * 1e12 == 1.56914e3 == 1569.14 USDC/ETH.
-        let sqrt_ratio: U256FD128 = response.sqrt_ratio.into();
-        Ok(PairRatio(sqrt_ratio.squared()))
+    /// Returns an error if the RPC call fails
+    pub async fn read_pool_price(
+        &self,
+        pool: &PoolKey,
+        block_id: BlockId,
+    ) -> Result<PairRatio, Error> {
+        let mut prices = self
+            .read_pool_prices(std::slice::from_ref(pool), block_id)
+            .await?;
+        Ok(prices.remove(0))
+    }
+
+    /// Reads the prices of many pools at a block, in as few requests as possible.
+    ///
+    /// Prices are returned in the order of `pools`.
+    ///
+    /// # Errors
+    /// Returns an error if one of the RPC calls fails
+    pub async fn read_pool_prices(
+        &self,
+        pools: &[PoolKey],
+        block_id: BlockId,
+    ) -> Result<Vec<PairRatio>, Error> {
+        Ok(self
+            .pool_prices(pools, block_id)
+            .await?
+            .iter()
+            .map(pair_ratio)
+            .collect())
+    }
+
+    /// Reads the price and the active liquidity of a pool at a block.
+    ///
+    /// # Errors
+    /// Returns an error if the RPC calls fail
+    pub async fn read_pool_state(
+        &self,
+        pool: &PoolKey,
+        block_id: BlockId,
+    ) -> Result<PoolState, Error> {
+        let cached = self
+            .cache_at(block_id)
+            .and_then(|cache| cache.state(pool).cloned());
+        if let Some(state) = cached {
+            return Ok(state);
+        }
+
+        let state =
+            read_pool_state(&self.rpc_client, self.contract_address, pool, block_id).await?;
+        if let Some(mut cache) = self.cache_at(block_id) {
+            cache.insert_state(pool.clone(), state.clone());
+        }
+        Ok(state)
+    }
+
+    /// Quotes a swap of `amount_in` of `token_in` in a pool, from its state at a block.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidSwap`] if the swap cannot be quoted, or an error if the RPC calls
+    /// fail
+    pub async fn quote_swap(
+        &self,
+        pool: &PoolKey,
+        token_in: Felt,
+        amount_in: u128,
+        block_id: BlockId,
+    ) -> Result<Quote, Error> {
+        let state = self.read_pool_state(pool, block_id).await?;
+        quote::quote(pool, &state, token_in, amount_in).ok_or(Error::InvalidSwap)
+    }
+
+    /// Reads the pools of every pair, then all their prices at `block_id` at once, in as few
+    /// batches as the node accepts.
+    ///
+    /// The prices are kept in the cache when `block_id` is the synced block, so that routes at
+    /// that block then read no pool. Pairs whose pools cannot be fetched are skipped, routes
+    /// through them fail on their own.
+    ///
+    /// # Errors
+    /// Returns an error if the prices cannot be read
+    pub async fn prefetch(&self, pairs: &[(Felt, Felt)], block_id: BlockId) -> Result<(), Error> {
+        let pools = stream::iter(pairs)
+            .map(|(token0, token1)| self.get_pools(*token0, *token1))
+            .buffer_unordered(CONCURRENT_API_REQUESTS)
+            .collect::<Vec<_>>()
+            .await;
+
+        let keys = pools
+            .into_iter()
+            .filter_map(Result::ok)
+            .flatten()
+            .map(|pool| pool.key)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        self.read_pool_prices(&keys, block_id).await?;
+
+        Ok(())
+    }
+
+    /// The pools of a pair of tokens, with their prices at a block read in one batch.
+    async fn pools_with_prices(
+        &self,
+        token0: Felt,
+        token1: Felt,
+        block_id: BlockId,
+    ) -> Result<Vec<(Pool, PairRatio)>, Error> {
+        let pools = self.get_pools(token0, token1).await?;
+        let keys = pools
+            .iter()
+            .map(|pool| pool.key.clone())
+            .collect::<Vec<_>>();
+        let prices = self.read_pool_prices(&keys, block_id).await?;
+
+        Ok(pools.into_iter().zip(prices).collect())
+    }
+
+    /// Finds the price of `quote` in `base`, going through one of the `intermediates` when the
+    /// pair has no direct pool.
+    ///
+    /// Every candidate path is evaluated, and the one with the most liquidity is returned, the
+    /// shortest one on a tie. For each hop, the pool with the best score of the `strategy` is
+    /// used, pools without enough liquidity are ignored. Pools are read at `block_id`.
+    ///
+    /// Each hop reads its prices in a batch, unless they were read beforehand with
+    /// [`Self::prefetch`]. A path whose pools cannot be read is skipped, so that a failing intermediate token does
+    /// not prevent the other paths from pricing the token.
+    ///
+    /// # Errors
+    /// Returns [`Error::PoolNotFound`] if no route exists, or the error of a path that failed
+    /// when no path succeeded
+    pub async fn get_route(
+        &self,
+        base: Felt,
+        quote: Felt,
+        intermediates: &[Felt],
+        strategy: &ScoringStrategy,
+        block_id: BlockId,
+    ) -> Result<Route, Error> {
+        let mut best: Option<Route> = None;
+        let mut failure = None;
+
+        'path_loop: for path in candidate_paths(base, quote, intermediates) {
+            let mut hops = Vec::with_capacity(path.len() - 1);
+            // Amount of the current token for one base token
+            let mut ratio = PairRatio(U256FD128::from_whole(1));
+            let mut liquidity = f64::INFINITY;
+
+            for pair in path.windows(2) {
+                let pools = match self.pools_with_prices(pair[0], pair[1], block_id).await {
+                    Ok(pools) => pools,
+                    Err(err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(
+                            "Skipping a route of {:#x} in {:#x}, the pools of {:#x} and {:#x} cannot be read: {}",
+                            quote,
+                            base,
+                            pair[0],
+                            pair[1],
+                            err
+                        );
+                        failure = Some(err);
+                        continue 'path_loop;
+                    }
+                };
+
+                let mut scored = Vec::new();
+                for (pool, price) in pools {
+                    let hop = Hop {
+                        from: pair[0],
+                        to: pair[1],
+                        pool: pool.key.clone(),
+                    };
+                    let Some(price) = hop.orient(price) else {
+                        continue;
+                    };
+
+                    let score = strategy.score(&pool, pair[0], &ratio);
+                    scored.push(((hop, price), score));
+                }
+
+                let Some(((hop, price), score)) = strategy.best(scored) else {
+                    continue 'path_loop;
+                };
+                ratio = PairRatio(ratio.0 * price.0);
+                liquidity = liquidity.min(score.liquidity);
+                hops.push(hop);
+            }
+
+            // Paths come from the shortest, so a longer one must be strictly better
+            if best
+                .as_ref()
+                .is_none_or(|route| liquidity > route.liquidity)
+            {
+                best = Some(Route {
+                    hops,
+                    ratio,
+                    liquidity,
+                });
+            }
+        }
+
+        best.ok_or_else(|| failure.unwrap_or(Error::PoolNotFound))
+    }
+}
+
+/// Converts the price read from a pool into the amount of token1 for one token0.
+fn pair_ratio(response: &LiquidityResponse) -> PairRatio {
+    // The value sqrt_ratio is a 64.128 fixed point number.
+    // To convert it to a price, first divide it by 2**128, then square it to get the price.
+    // Since USDC is token1, this value is the price of the pool in USDC/ETH.
+    // (0x029895c9cbfca44f2c46e6e9b5459b / 2**128)**2 == 1.56914... ×10^-9.
+    // To adjust for display, we have to account for the decimal difference between the USDC and ETH tokens.
+    // Because USDC has 6 decimals and ETH has 18 decimals, we need to scale it up by 10**(18-6) to be human readable.
+    // 1.56914e-9 * 1e12 == 1.56914e3 == 1569.14 USDC/ETH.
+    let sqrt_ratio: U256FD128 = response.sqrt_ratio.into();
+    PairRatio(sqrt_ratio.squared())
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::BlockTag;
+
+    use super::*;
+    use crate::testing::{MockApi, MockNode, CORE, ETH, STRK, USDC};
+
+    const LATEST: BlockId = BlockId::Tag(BlockTag::Latest);
+
+    fn eth_usdc() -> PoolKey {
+        PoolKey {
+            token0: ETH,
+            token1: USDC,
+            fee: 0x20c4_9ba5_e353_f800_0000_0000_0000_0000,
+            tick_spacing: 1000,
+            extension: Felt::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_route() {
+        let node = MockNode::new();
+        let provider = node.provider();
+        let api = MockApi::new().await;
+        let client = EkuboClient::new(CORE, &provider, api.url());
+
+        // The only STRK/USDC pool is thin and skewed, so STRK is priced through ETH
+        let route = client
+            .get_route(USDC, STRK, &[ETH], &ScoringStrategy::default(), LATEST)
+            .await
+            .unwrap();
+
+        assert_eq!(route.hops.len(), 2);
+        assert_eq!(route.hops[0].pool, eth_usdc());
+        assert_eq!(route.hops[1].to, STRK);
+        // 1 USDC is 4e-4 ETH, which is 2 STRK, in their smallest units
+        let ratio = f64::from(route.ratio.0);
+        assert!((ratio / 2e12 - 1.0).abs() < 1e-9, "{ratio}");
+        // One batch for the pools of each hop of both paths
+        assert_eq!(node.batches(), 3);
+
+        // Without intermediates, the thin pool is the only route
+        let route = client
+            .get_route(USDC, STRK, &[], &ScoringStrategy::default(), LATEST)
+            .await
+            .unwrap();
+        assert_eq!(route.hops.len(), 1);
+        assert!((route.liquidity - 2e9).abs() < 1e-3, "{}", route.liquidity);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch() {
+        let node = MockNode::new();
+        let provider = node.provider();
+        let api = MockApi::new().await;
+        let client = EkuboClient::new(CORE, &provider, api.url());
+
+        let block_id = BlockId::Number(client.sync_block().await.unwrap());
+        client
+            .prefetch(&[(USDC, STRK), (USDC, ETH), (ETH, STRK)], block_id)
+            .await
+            .unwrap();
+        // Every price of the snapshot in a single batch
+        assert_eq!(node.batches(), 1);
+
+        let route = client
+            .get_route(USDC, STRK, &[ETH], &ScoringStrategy::default(), block_id)
+            .await
+            .unwrap();
+        assert_eq!(route.hops.len(), 2);
+        // The route reads nothing more
+        assert_eq!(node.batches(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_route_failing_path() {
+        let node = MockNode::new();
+        let provider = node.provider();
+        let api = MockApi::new().await;
+        let client = EkuboClient::new(CORE, &provider, api.url());
+        // The API has no answer for the pools of this token
+        let unknown = Felt::THREE;
+
+        // The direct pool still prices the token
+        let route = client
+            .get_route(USDC, ETH, &[unknown], &ScoringStrategy::default(), LATEST)
+            .await
+            .unwrap();
+        assert_eq!(route.hops.len(), 1);
+        assert_eq!(route.hops[0].pool, eth_usdc());
+
+        // Without any other path, the error is returned
+        assert!(matches!(
+            client
+                .get_route(USDC, unknown, &[], &ScoringStrategy::default(), LATEST)
+                .await,
+            Err(Error::HttpError(_) | Error::ApiError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_pool_prices() {
+        let node = MockNode::new();
+        let provider = node.provider();
+        let api = MockApi::new().await;
+        let client = EkuboClient::new(CORE, &provider, api.url());
+
+        let block_number = client.sync_block().await.unwrap();
+        let at = BlockId::Number(block_number);
+        let pools = [
+            client.get_pools(ETH, USDC).await.unwrap(),
+            client.get_pools(STRK, ETH).await.unwrap(),
+        ]
+        .concat()
+        .into_iter()
+        .map(|pool| pool.key)
+        .collect::<Vec<_>>();
+        client.read_pool_price(&pools[2], at).await.unwrap();
+
+        // Reads stay at the given block when the node moves on
+        node.set_block_number(block_number + 1);
+        let prices = client.read_pool_prices(&pools, at).await.unwrap();
+        assert_eq!(prices.len(), 3);
+        assert_eq!(
+            prices[2].0,
+            client.read_pool_price(&pools[2], at).await.unwrap().0
+        );
+        assert!(prices[0].0 != prices[1].0);
+
+        // The cached pool is not read again, the others are read in one batch
+        assert_eq!(node.requests(), 4);
+        assert_eq!(node.batches(), 2);
+        assert!(node
+            .call_blocks()
+            .iter()
+            .all(|block_id| *block_id == serde_json::json!({ "block_number": block_number })));
+    }
+
+    #[tokio::test]
+    async fn test_pool_cache() {
+        let node = MockNode::new();
+        let provider = node.provider();
+        let api = MockApi::new().await;
+        let client = EkuboClient::new(CORE, &provider, api.url());
+
+        // Reads at the latest block always reach the node
+        client.read_pool_price(&eth_usdc(), LATEST).await.unwrap();
+        client.read_pool_price(&eth_usdc(), LATEST).await.unwrap();
+        assert_eq!(node.requests(), 2);
+
+        let first = client.sync_block().await.unwrap();
+        assert_eq!(first, node.block_number());
+        let at_first = BlockId::Number(first);
+        let state = client.read_pool_state(&eth_usdc(), at_first).await.unwrap();
+        let price = client.read_pool_price(&eth_usdc(), at_first).await.unwrap();
+        client.read_pool_state(&eth_usdc(), at_first).await.unwrap();
+        // The block number, then the liquidity and the price
+        assert_eq!(node.requests(), 5);
+        assert_eq!(price.0, U256FD128::from(state.sqrt_ratio).squared());
+
+        // A new block reads the pool again, at that block
+        let second = first + 1;
+        node.set_block_number(second);
+        client.sync_block().await.unwrap();
+        client
+            .read_pool_state(&eth_usdc(), BlockId::Number(second))
+            .await
+            .unwrap();
+        assert_eq!(node.requests(), 8);
+
+        // A snapshot still at the previous block does not mix in the cached pools
+        client.read_pool_state(&eth_usdc(), at_first).await.unwrap();
+        assert_eq!(node.requests(), 10);
+
+        // Reads are pinned to the block they are given
+        let at = |block_number: u64| serde_json::json!({ "block_number": block_number });
+        assert_eq!(
+            node.call_blocks(),
+            [
+                vec![serde_json::json!("latest"); 2],
+                vec![at(first); 2],
+                vec![at(second); 2],
+                vec![at(first); 2],
+            ]
+            .concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_swap() {
+        let node = MockNode::new();
+        let provider = node.provider();
+        let api = MockApi::new().await;
+        let client = EkuboClient::new(CORE, &provider, api.url());
+
+        // 1 USDC is at most 4e-4 ETH
+        let quote = client
+            .quote_swap(&eth_usdc(), USDC, 1_000_000, LATEST)
+            .await
+            .unwrap();
+        assert!(quote.fee > 0);
+        assert!(quote.amount_out > 0 && quote.amount_out < 400_000_000_000_000);
+        // A small swap moves the price by a couple of ticks
+        assert!(quote.within_tick_range);
+
+        let unknown = PoolKey {
+            fee: 0,
+            ..eth_usdc()
+        };
+        assert!(matches!(
+            client.read_pool_price(&unknown, LATEST).await,
+            Err(Error::RpcError(_))
+        ));
     }
 }
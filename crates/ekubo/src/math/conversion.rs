@@ -0,0 +1,441 @@
+//! Conversions of [`U256FD128`] to and from decimal strings and floats, with explicit rounding.
+//!
+//! Every value has a finite decimal expansion of at most 128 digits, so
+//! [`U256FD128::to_decimal_string`] is lossless and parses back to the same value.
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss
+)]
+
+use std::cmp::Ordering;
+
+use ekubo_sdk::math::uint::U256;
+use thiserror::Error;
+
+use super::u256fd128::{U256FD128, U512};
+
+const DECIMAL_DIGITS: usize = 128;
+
+/// How to round a value that cannot be represented exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards negative infinity.
+    Floor,
+    /// Towards positive infinity.
+    Ceil,
+    /// To the nearest value, ties to the even one.
+    HalfEven,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("Invalid decimal number")]
+    Invalid,
+    #[error("Number out of range")]
+    OutOfRange,
+}
+
+impl Rounding {
+    /// Whether the magnitude of a truncated value must be increased.
+    ///
+    /// `half` compares the dropped part with half a unit of the last kept digit, and `odd` tells
+    /// whether that digit is odd.
+    fn rounds_up(self, negative: bool, odd: bool, half: Ordering, inexact: bool) -> bool {
+        match self {
+            Rounding::Floor => negative && inexact,
+            Rounding::Ceil => !negative && inexact,
+            Rounding::HalfEven => half == Ordering::Greater || (half == Ordering::Equal && odd),
+        }
+    }
+}
+
+/// Builds a value from its magnitude, `None` if it does not fit.
+fn from_magnitude(magnitude: U512, negative: bool) -> Option<U256FD128> {
+    let limit = U512::one() << 255;
+    if magnitude > limit || (magnitude == limit && !negative) {
+        return None;
+    }
+
+    let value = U256FD128::new(U256([
+        magnitude.0[0],
+        magnitude.0[1],
+        magnitude.0[2],
+        magnitude.0[3],
+    ]));
+    Some(if negative && !magnitude.is_zero() {
+        value.neg()
+    } else {
+        value
+    })
+}
+
+/// Splits the magnitude of a value into its whole part and its 128 decimal digits.
+fn decimal_parts(value: &U256FD128) -> (U512, U512) {
+    let magnitude = value.abs().raw();
+    let whole = U512::from(magnitude >> 128);
+    // x / 2^128 == x * 5^128 / 10^128
+    let fraction = U512::from(magnitude & ((U256::one() << 128) - 1))
+        * U512::from(5u64).pow(U512::from(DECIMAL_DIGITS as u64));
+
+    (whole, fraction)
+}
+
+impl U256FD128 {
+    /// Formats the exact value of the number, without trailing zeros.
+    #[must_use]
+    pub fn to_decimal_string(&self) -> String {
+        let (whole, fraction) = decimal_parts(self);
+        let sign = if self.is_negative() { "-" } else { "" };
+
+        let fraction = format!("{:0>DECIMAL_DIGITS$}", fraction.to_string());
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            format!("{sign}{whole}")
+        } else {
+            format!("{sign}{whole}.{fraction}")
+        }
+    }
+
+    /// Formats the number with exactly `decimals` digits after the point.
+    #[must_use]
+    pub fn to_decimal_string_rounded(&self, decimals: usize, rounding: Rounding) -> String {
+        let negative = self.is_negative();
+        let (mut whole, fraction) = decimal_parts(self);
+
+        let digits = decimals.min(DECIMAL_DIGITS);
+        let unit = U512::exp10(DECIMAL_DIGITS - digits);
+        let mut kept = fraction / unit;
+        let dropped = fraction % unit;
+
+        let odd = if digits == 0 {
+            whole.bit(0)
+        } else {
+            kept.bit(0)
+        };
+        if rounding.rounds_up(negative, odd, (dropped * 2).cmp(&unit), !dropped.is_zero()) {
+            kept += U512::one();
+            if kept == U512::exp10(digits) {
+                kept = U512::zero();
+                whole += U512::one();
+            }
+        }
+
+        let sign = if negative && !(whole.is_zero() && kept.is_zero()) {
+            "-"
+        } else {
+            ""
+        };
+        if decimals == 0 {
+            format!("{sign}{whole}")
+        } else {
+            format!(
+                "{sign}{whole}.{:0>digits$}{}",
+                kept.to_string(),
+                "0".repeat(decimals - digits)
+            )
+        }
+    }
+
+    /// Parses a decimal number, such as `-12.5`, rounding it to the closest representable values.
+    ///
+    /// # Errors
+    /// Returns an error if the string is not a decimal number, or if it does not fit.
+    pub fn from_decimal_str(value: &str, rounding: Rounding) -> Result<Self, ParseError> {
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let is_number = |digits: &str| digits.bytes().all(|byte| byte.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_number(whole) || !is_number(fraction) {
+            return Err(ParseError::Invalid);
+        }
+
+        let parse = |digits: &str| {
+            if digits.is_empty() {
+                Ok(U512::zero())
+            } else {
+                U512::from_dec_str(digits).map_err(|_| ParseError::OutOfRange)
+            }
+        };
+        let whole = parse(whole)?;
+        if whole.bits() > 128 {
+            return Err(ParseError::OutOfRange);
+        }
+
+        // Digits past the 128th are worth less than the last bit
+        let (fraction, extra) = fraction.split_at(fraction.len().min(DECIMAL_DIGITS));
+        let sticky = extra.bytes().any(|byte| byte != b'0');
+
+        // fraction / 10^k * 2^128 == fraction * 2^(128 - k) / 5^k
+        let numerator = parse(fraction)? << (DECIMAL_DIGITS - fraction.len());
+        let denominator = U512::from(5u64).pow(U512::from(fraction.len() as u64));
+        let mut magnitude = (whole << 128) + numerator / denominator;
+        let remainder = numerator % denominator;
+
+        let half = match (remainder * 2).cmp(&denominator) {
+            Ordering::Equal if sticky => Ordering::Greater,
+            half => half,
+        };
+        if rounding.rounds_up(
+            negative,
+            magnitude.bit(0),
+            half,
+            sticky || !remainder.is_zero(),
+        ) {
+            magnitude += U512::one();
+        }
+
+        from_magnitude(magnitude, negative).ok_or(ParseError::OutOfRange)
+    }
+
+    /// Converts the number to the closest float in the given direction.
+    #[must_use]
+    pub fn to_f64(&self, rounding: Rounding) -> f64 {
+        let negative = self.is_negative();
+        let magnitude = self.abs().raw();
+        if magnitude.is_zero() {
+            return 0.0;
+        }
+
+        // Floats have 53 significant bits
+        let shift = magnitude.bits().saturating_sub(53);
+        let mut mantissa = (magnitude >> shift).low_u64();
+        if shift > 0 {
+            let dropped = magnitude & ((U256::one() << shift) - 1);
+            let half = dropped.cmp(&(U256::one() << (shift - 1)));
+
+            if rounding.rounds_up(negative, mantissa & 1 == 1, half, !dropped.is_zero()) {
+                mantissa += 1;
+            }
+        }
+
+        let value = mantissa as f64 * 2f64.powi(shift as i32 - 128);
+        if negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    /// Converts a float, rounding it if it has bits below 2^-128.
+    ///
+    /// Returns `None` if the float is not finite or does not fit.
+    #[must_use]
+    pub fn from_f64(value: f64, rounding: Rounding) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        let bits = value.to_bits();
+        let negative = bits >> 63 == 1;
+        let exponent = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1 << 52) - 1);
+        // value == mantissa * 2^exponent
+        let (mantissa, exponent) = if exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | 1 << 52, exponent - 1075)
+        };
+
+        let shift = exponent + 128;
+        let magnitude = if shift >= 0 {
+            if shift > 256 {
+                return None;
+            }
+            U512::from(mantissa) << shift as usize
+        } else {
+            let shift = shift.unsigned_abs();
+            let (kept, dropped, half) = if shift > 54 {
+                // Less than half of the last bit
+                (0, mantissa, Ordering::Less)
+            } else {
+                let dropped = mantissa & ((1 << shift) - 1);
+                (mantissa >> shift, dropped, dropped.cmp(&(1 << (shift - 1))))
+            };
+
+            let round_up = rounding.rounds_up(negative, kept & 1 == 1, half, dropped != 0);
+            U512::from(kept + u64::from(round_up))
+        };
+
+        from_magnitude(magnitude, negative)
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::str::FromStr;
+
+    use bigdecimal::{num_bigint::BigInt, BigDecimal, RoundingMode};
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Exact value of a number.
+    pub(crate) fn to_big(value: U256FD128) -> BigDecimal {
+        let raw = value.raw();
+        let mut digits = (0..4).rev().fold(BigInt::from(0), |acc, i| {
+            (acc << 64) + BigInt::from(raw.0[i])
+        });
+        if value.is_negative() {
+            digits -= BigInt::from(1) << 256;
+        }
+
+        BigDecimal::new(digits * BigInt::from(5).pow(128), 128)
+    }
+
+    /// Exact value of a float.
+    fn f64_to_big(value: f64) -> BigDecimal {
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = if exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | 1 << 52, exponent - 1075)
+        };
+
+        let sign = if value.is_sign_negative() { -1 } else { 1 };
+        let mantissa = BigInt::from(mantissa) * sign;
+        if exponent >= 0 {
+            BigDecimal::from(mantissa << exponent)
+        } else {
+            BigDecimal::new(
+                mantissa * BigInt::from(5).pow(exponent.unsigned_abs() as u32),
+                -exponent,
+            )
+        }
+    }
+
+    fn mode(rounding: Rounding) -> RoundingMode {
+        match rounding {
+            Rounding::Floor => RoundingMode::Floor,
+            Rounding::Ceil => RoundingMode::Ceiling,
+            Rounding::HalfEven => RoundingMode::HalfEven,
+        }
+    }
+
+    pub(crate) fn any_value() -> impl Strategy<Value = U256FD128> {
+        any::<[u64; 4]>().prop_map(|words| U256FD128::new(U256(words)))
+    }
+
+    fn any_rounding() -> impl Strategy<Value = Rounding> {
+        prop_oneof![
+            Just(Rounding::Floor),
+            Just(Rounding::Ceil),
+            Just(Rounding::HalfEven)
+        ]
+    }
+
+    #[test]
+    fn test_decimal_string() {
+        let half = U256FD128::from_whole(1) / U256FD128::from_whole(2);
+        assert_eq!(half.to_decimal_string(), "0.5");
+        assert_eq!(U256FD128::from_whole(3).neg().to_decimal_string(), "-3");
+        assert_eq!(half.to_decimal_string_rounded(0, Rounding::HalfEven), "0");
+        assert_eq!(
+            half.neg().to_decimal_string_rounded(0, Rounding::Floor),
+            "-1"
+        );
+        assert_eq!(half.to_decimal_string_rounded(2, Rounding::Floor), "0.50");
+        assert_eq!(
+            U256FD128::from_decimal_str("-12.25", Rounding::Floor),
+            Ok(U256FD128::from_whole(49).neg() / U256FD128::from_whole(4))
+        );
+        assert_eq!(
+            U256FD128::from_decimal_str("1.2.3", Rounding::Floor),
+            Err(ParseError::Invalid)
+        );
+        assert_eq!(
+            U256FD128::from_decimal_str(&"9".repeat(40), Rounding::Floor),
+            Err(ParseError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_f64() {
+        let third = U256FD128::from_whole(1) / U256FD128::from_whole(3);
+        assert!(third.to_f64(Rounding::Floor) < third.to_f64(Rounding::Ceil));
+        assert_eq!(
+            U256FD128::from_f64(-2.5, Rounding::Floor),
+            Some(U256FD128::from_whole(5).neg() / U256FD128::from_whole(2))
+        );
+        assert_eq!(U256FD128::from_f64(f64::NAN, Rounding::Floor), None);
+        assert_eq!(U256FD128::from_f64(1e40, Rounding::Floor), None);
+        assert_eq!(
+            U256FD128::from_f64(1e-40, Rounding::Ceil),
+            Some(U256FD128::new(U256::one()))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn decimal_string_is_exact(value in any_value(), rounding in any_rounding()) {
+            let string = value.to_decimal_string();
+            prop_assert_eq!(BigDecimal::from_str(&string).unwrap(), to_big(value));
+            prop_assert_eq!(U256FD128::from_decimal_str(&string, rounding), Ok(value));
+        }
+
+        #[test]
+        fn rounded_decimal_string(
+            value in any_value(),
+            decimals in 0usize..140,
+            rounding in any_rounding(),
+        ) {
+            let string = value.to_decimal_string_rounded(decimals, rounding);
+            prop_assert_eq!(
+                BigDecimal::from_str(&string).unwrap(),
+                to_big(value).with_scale_round(decimals as i64, mode(rounding))
+            );
+        }
+
+        #[test]
+        fn parse_decimal(whole in "[0-9]{0,30}", fraction in "[0-9]{1,150}", negative: bool) {
+            let string = format!("{}{whole}.{fraction}", if negative { "-" } else { "" });
+            let exact = BigDecimal::from_str(&string).unwrap();
+
+            let floor = to_big(U256FD128::from_decimal_str(&string, Rounding::Floor).unwrap());
+            let ceil = to_big(U256FD128::from_decimal_str(&string, Rounding::Ceil).unwrap());
+            let nearest = to_big(U256FD128::from_decimal_str(&string, Rounding::HalfEven).unwrap());
+            let ulp = to_big(U256FD128::new(U256::one()));
+
+            prop_assert!(floor <= exact && exact <= ceil);
+            prop_assert!(&ceil - &floor <= ulp);
+            prop_assert!((&nearest - &exact).abs() * BigDecimal::from(2) <= ulp);
+        }
+
+        #[test]
+        fn to_f64_bounds(value in any_value()) {
+            let exact = to_big(value);
+            let floor = value.to_f64(Rounding::Floor);
+            let ceil = value.to_f64(Rounding::Ceil);
+            let nearest = value.to_f64(Rounding::HalfEven);
+
+            prop_assert!(f64_to_big(floor) <= exact && exact <= f64_to_big(ceil));
+            prop_assert!(ceil <= floor.next_up());
+            prop_assert!(nearest.to_bits() == floor.to_bits() || nearest.to_bits() == ceil.to_bits());
+            prop_assert!(
+                (f64_to_big(nearest) - &exact).abs() <= (f64_to_big(floor) - &exact).abs()
+                    && (f64_to_big(nearest) - &exact).abs() <= (f64_to_big(ceil) - &exact).abs()
+            );
+        }
+
+        #[test]
+        fn from_f64_bounds(value in -1e38f64..1e38, rounding in any_rounding()) {
+            let exact = f64_to_big(value);
+            let floor = to_big(U256FD128::from_f64(value, Rounding::Floor).unwrap());
+            let ceil = to_big(U256FD128::from_f64(value, Rounding::Ceil).unwrap());
+            let ulp = to_big(U256FD128::new(U256::one()));
+
+            prop_assert!(floor <= exact && exact <= ceil);
+            prop_assert!(&ceil - &floor <= ulp);
+
+            // Floats with a large enough exponent are exact
+            if value.abs() >= 1.0 {
+                prop_assert_eq!(to_big(U256FD128::from_f64(value, rounding).unwrap()), exact);
+            }
+        }
+    }
+}
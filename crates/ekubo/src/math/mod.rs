@@ -1 +1,4 @@
+pub mod conversion;
+pub mod tick;
+pub mod transcendental;
 pub mod u256fd128;
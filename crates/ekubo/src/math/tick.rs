@@ -0,0 +1,158 @@
+//! Conversions between ticks and the square root prices of Ekubo pools.
+
+use ekubo_sdk::math::uint::U256;
+
+use super::u256fd128::U256FD128;
+
+/// Smallest tick of a pool.
+pub const MIN_TICK: i32 = -88_722_883;
+
+/// Largest tick of a pool.
+pub const MAX_TICK: i32 = 88_722_883;
+
+/// ln(1.000001), each tick moves the price by 0.0001%.
+const LN_TICK_BASE: U256FD128 =
+    U256FD128::new(U256([0xa9e6_5cd0_e231_0199, 0x0000_10c6_f713_f927, 0, 0]));
+
+/// Computes the square root of the price at a tick, as a 64.128 number like a pool's `sqrt_ratio`.
+///
+/// Returns `None` if the tick is out of the range of a pool.
+#[must_use]
+pub fn tick_to_sqrt_ratio(tick: i32) -> Option<U256FD128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+
+    // sqrt(1.000001^tick) = e^(tick * ln(1.000001) / 2)
+    let exponent =
+        LN_TICK_BASE * U256FD128::from_whole(tick.unsigned_abs().into()) / U256FD128::from_whole(2);
+    Some(if tick < 0 { exponent.neg() } else { exponent }.exp())
+}
+
+/// Computes the greatest tick whose square root price is at most `sqrt_ratio`.
+///
+/// Returns `None` if the price is out of the range of a pool.
+#[must_use]
+pub fn sqrt_ratio_to_tick(sqrt_ratio: U256FD128) -> Option<i32> {
+    if sqrt_ratio < tick_to_sqrt_ratio(MIN_TICK)? || sqrt_ratio > tick_to_sqrt_ratio(MAX_TICK)? {
+        return None;
+    }
+
+    // tick = 2 * ln(sqrt_ratio) / ln(1.000001), off by at most one from rounding
+    let ln = sqrt_ratio.ln();
+    let mut tick = floor((ln + ln) / LN_TICK_BASE).clamp(MIN_TICK, MAX_TICK);
+    while tick > MIN_TICK && tick_to_sqrt_ratio(tick)? > sqrt_ratio {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && tick_to_sqrt_ratio(tick + 1)? <= sqrt_ratio {
+        tick += 1;
+    }
+
+    Some(tick)
+}
+
+/// Rounds a number within the range of ticks towards negative infinity.
+fn floor(value: U256FD128) -> i32 {
+    let abs = value.abs();
+    let whole = i32::try_from((abs.raw() >> 128).low_u64()).expect("small number");
+    if !value.is_negative() {
+        whole
+    } else if abs.raw().low_u128() == 0 {
+        -whole
+    } else {
+        -whole - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bigdecimal::BigDecimal;
+    use proptest::prelude::*;
+
+    use super::super::conversion::{tests::to_big, Rounding};
+    use super::*;
+
+    const LN_TICK_BASE_REFERENCE: &str =
+        "0.00000099999950000033333308333353333316666680952368452392063482063501154392821";
+
+    fn assert_close(value: &BigDecimal, expected: &BigDecimal) {
+        let tolerance = expected.abs() * BigDecimal::from_str("1e-28").unwrap();
+        assert!(
+            (value - expected).abs() <= tolerance,
+            "{value} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn test_ln_tick_base() {
+        let base = U256FD128::from_decimal_str("1.000001", Rounding::HalfEven).unwrap();
+        let reference = BigDecimal::from_str(LN_TICK_BASE_REFERENCE).unwrap();
+
+        assert_close(&to_big(LN_TICK_BASE), &reference);
+        assert_close(&to_big(base.ln()), &reference);
+        assert_close(
+            &to_big(base.pow(1000).log_base(base)),
+            &BigDecimal::from(1000),
+        );
+    }
+
+    #[test]
+    fn test_tick_to_sqrt_ratio() {
+        assert_eq!(tick_to_sqrt_ratio(0), Some(U256FD128::from_whole(1)));
+        assert!(tick_to_sqrt_ratio(MAX_TICK + 1).is_none());
+        assert!(tick_to_sqrt_ratio(MIN_TICK - 1).is_none());
+
+        // The price range of a pool is about 2^-128 to 2^128
+        let max = tick_to_sqrt_ratio(MAX_TICK).unwrap();
+        let min = tick_to_sqrt_ratio(MIN_TICK).unwrap();
+        assert_eq!(max.raw().bits(), 192);
+        assert_eq!(min.raw().bits(), 65);
+    }
+
+    #[test]
+    fn test_sqrt_ratio_to_tick() {
+        assert_eq!(sqrt_ratio_to_tick(U256FD128::from_whole(1)), Some(0));
+        assert_eq!(
+            sqrt_ratio_to_tick(tick_to_sqrt_ratio(MAX_TICK).unwrap()),
+            Some(MAX_TICK)
+        );
+        assert_eq!(
+            sqrt_ratio_to_tick(tick_to_sqrt_ratio(MIN_TICK).unwrap()),
+            Some(MIN_TICK)
+        );
+        assert!(sqrt_ratio_to_tick(U256FD128::ZERO).is_none());
+        assert!(sqrt_ratio_to_tick(U256FD128::from_whole(1 << 65)).is_none());
+
+        let below_one = U256FD128::new(U256FD128::from_whole(1).raw() - 1);
+        assert_eq!(sqrt_ratio_to_tick(below_one), Some(-1));
+    }
+
+    proptest! {
+        #[test]
+        fn tick_round_trip(tick in MIN_TICK..=MAX_TICK) {
+            let sqrt_ratio = tick_to_sqrt_ratio(tick).unwrap();
+            prop_assert_eq!(sqrt_ratio_to_tick(sqrt_ratio), Some(tick));
+        }
+
+        #[test]
+        fn tick_is_monotonic(tick in MIN_TICK..MAX_TICK) {
+            let sqrt_ratio = tick_to_sqrt_ratio(tick).unwrap();
+            let next = tick_to_sqrt_ratio(tick + 1).unwrap();
+            prop_assert!(sqrt_ratio < next);
+            prop_assert_eq!(
+                sqrt_ratio_to_tick(U256FD128::new(next.raw() - 1)),
+                Some(tick)
+            );
+        }
+
+        #[test]
+        fn tick_matches_reference(tick in MIN_TICK..=MAX_TICK) {
+            let exponent = BigDecimal::from(tick)
+                * BigDecimal::from_str(LN_TICK_BASE_REFERENCE).unwrap()
+                / BigDecimal::from(2);
+            assert_close(&to_big(tick_to_sqrt_ratio(tick).unwrap()), &exponent.exp());
+        }
+    }
+}
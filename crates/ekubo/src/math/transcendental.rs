@@ -0,0 +1,219 @@
+//! Powers, exponential and logarithms of [`U256FD128`], for liquidity and tick math.
+
+use ekubo_sdk::math::uint::U256;
+
+use super::u256fd128::U256FD128;
+
+/// ln(2)
+const LN_2: U256FD128 = U256FD128::new(U256([0xc9e3_b398_03f2_f6af, 0xb172_17f7_d1cf_79ab, 0, 0]));
+
+/// sqrt(2)
+const SQRT_2: U256FD128 =
+    U256FD128::new(U256([0xb2fb_1366_ea95_7d3e, 0x6a09_e667_f3bc_c908, 1, 0]));
+
+impl U256FD128 {
+    /// Raises the number to an integer power, by squaring.
+    ///
+    /// # Panics
+    /// Panics if the result overflows, or if a negative power of zero is requested.
+    #[must_use]
+    pub fn pow(&self, exponent: i32) -> Self {
+        let mut result = Self::from_whole(1);
+        let mut base = *self;
+        let mut remaining = exponent.unsigned_abs();
+
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result * base;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                base = base.squared();
+            }
+        }
+
+        if exponent < 0 {
+            Self::from_whole(1) / result
+        } else {
+            result
+        }
+    }
+
+    /// Calculates e to the power of the number.
+    ///
+    /// # Panics
+    /// Panics if the result overflows.
+    #[must_use]
+    pub fn exp(&self) -> Self {
+        // x = k * ln(2) + r, with |r| <= ln(2) / 2
+        let half = Self::from_whole(1) / Self::from_whole(2);
+        let k = (*self / LN_2 + if self.is_negative() { half.neg() } else { half }).trunc();
+        assert!(k < 127, "Exponential overflow");
+        let r = *self - LN_2 * Self::from_whole(k.unsigned_abs().into()).with_sign(k < 0);
+
+        // Taylor series of e^r, terms shrink quickly as |r| < 0.35
+        let mut result = Self::from_whole(1);
+        let mut term = Self::from_whole(1);
+        for n in 1..64 {
+            term = term * r / Self::from_whole(n);
+            if term == Self::ZERO {
+                break;
+            }
+            result = result + term;
+        }
+
+        if k >= 0 {
+            Self::new(result.raw() << k.unsigned_abs())
+        } else {
+            Self::new(result.raw() >> k.unsigned_abs())
+        }
+    }
+
+    /// Calculates the natural logarithm of the number.
+    ///
+    /// # Panics
+    /// Panics if the number is not positive.
+    #[must_use]
+    pub fn ln(&self) -> Self {
+        assert!(
+            self.sign() > 0,
+            "Cannot calculate the logarithm of a non positive number"
+        );
+
+        // x = m * 2^k, with m in [sqrt(2) / 2, sqrt(2))
+        let mut k = i32::try_from(self.raw().bits()).expect("at most 256 bits") - 129;
+        let mut m = if k >= 0 {
+            Self::new(self.raw() >> k.unsigned_abs())
+        } else {
+            Self::new(self.raw() << k.unsigned_abs())
+        };
+        if m > SQRT_2 {
+            m = Self::new(m.raw() >> 1);
+            k += 1;
+        }
+
+        // ln(m) = 2 * atanh(z) = 2 * (z + z^3 / 3 + z^5 / 5 + ...), with z = (m - 1) / (m + 1)
+        let one = Self::from_whole(1);
+        let z = (m - one) / (m + one);
+        let z_squared = z.squared();
+        let mut sum = z;
+        let mut power = z;
+        for n in 1..64u128 {
+            power = power * z_squared;
+            let term = power / Self::from_whole(2 * n + 1);
+            if term == Self::ZERO {
+                break;
+            }
+            sum = sum + term;
+        }
+
+        let k_ln_2 = LN_2 * Self::from_whole(k.unsigned_abs().into()).with_sign(k < 0);
+        sum + sum + k_ln_2
+    }
+
+    /// Calculates the logarithm of the number in the given base.
+    ///
+    /// # Panics
+    /// Panics if the number or the base is not positive, or if the base is one.
+    #[must_use]
+    pub fn log_base(&self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    /// Returns the whole part of the number, rounded towards zero.
+    fn trunc(&self) -> i32 {
+        let whole = i32::try_from((self.abs().raw() >> 128).low_u64()).expect("small number");
+        if self.is_negative() {
+            -whole
+        } else {
+            whole
+        }
+    }
+
+    fn with_sign(self, negative: bool) -> Self {
+        if negative && self != Self::ZERO {
+            self.neg()
+        } else {
+            self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bigdecimal::BigDecimal;
+    use proptest::prelude::*;
+
+    use super::super::conversion::{tests::to_big, Rounding};
+    use super::*;
+
+    fn assert_close(value: &BigDecimal, expected: &BigDecimal) {
+        let tolerance = expected.abs() * BigDecimal::from_str("1e-30").unwrap()
+            + BigDecimal::from_str("1e-36").unwrap();
+        assert!(
+            (value - expected).abs() <= tolerance,
+            "{value} is not close to {expected}"
+        );
+    }
+
+    fn decimal(value: &str) -> U256FD128 {
+        U256FD128::from_decimal_str(value, Rounding::HalfEven).unwrap()
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_close(
+            &to_big(LN_2),
+            &BigDecimal::from_str("0.693147180559945309417232121458176568075500134360255254120680")
+                .unwrap(),
+        );
+        assert_close(&to_big(SQRT_2.squared()), &2.into());
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(U256FD128::from_whole(3).pow(4), U256FD128::from_whole(81));
+        assert_eq!(U256FD128::from_whole(3).pow(0), U256FD128::from_whole(1));
+        assert_eq!(decimal("2").pow(-2), decimal("0.25"));
+        assert_eq!(decimal("-2").pow(3), decimal("-8"));
+    }
+
+    #[test]
+    fn test_exp_ln() {
+        assert_eq!(U256FD128::ZERO.exp(), U256FD128::from_whole(1));
+        assert_eq!(U256FD128::from_whole(1).ln(), U256FD128::ZERO);
+        assert_close(
+            &to_big(U256FD128::from_whole(1).exp()),
+            &BigDecimal::from_str("2.718281828459045235360287471352662497757247093699959574966968")
+                .unwrap(),
+        );
+        assert_close(
+            &to_big(U256FD128::from_whole(8).log_base(decimal("2"))),
+            &3.into(),
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn pow_matches_reference(whole in 0u128..(1 << 24), fraction: u128, exponent in 0i32..5) {
+            // Values below 2^24 so the result cannot overflow
+            let value = U256FD128::from_whole(whole) + U256FD128::new(U256::from(fraction));
+            let expected = (0..exponent).fold(BigDecimal::from(1), |acc, _| acc * to_big(value));
+            assert_close(&to_big(value.pow(exponent)), &expected);
+        }
+
+        #[test]
+        fn exp_matches_reference(value in -80.0f64..80.0) {
+            let value = U256FD128::from_f64(value, Rounding::HalfEven).unwrap();
+            assert_close(&to_big(value.exp()), &to_big(value).exp());
+        }
+
+        #[test]
+        fn ln_matches_reference(raw in 1u128.., shift in 0usize..120) {
+            let value = U256FD128::new(U256::from(raw) << shift);
+            assert_close(&to_big(value.ln()).exp(), &to_big(value));
+        }
+    }
+}
@@ -108,7 +108,7 @@ impl U256FD128 {
 
     /// Creates a new U256FD128 from a raw U256 value
     #[must_use]
-    pub fn new(value: U256) -> Self {
+    pub const fn new(value: U256) -> Self {
         Self(value)
     }
 
@@ -197,6 +197,48 @@ impl U256FD128 {
     pub fn squared(&self) -> Self {
         *self * *self
     }
+
+    /// Applies the sign of an operation to an absolute result, `None` if it does not fit.
+    fn signed_result(result: U512, negative: bool) -> Option<Self> {
+        // Keeps the sign bit free
+        if result.bits() > 255 {
+            return None;
+        }
+
+        let result = Self(U256([result.0[0], result.0[1], result.0[2], result.0[3]]));
+        // Zero has no negative, a result rounded down to zero stays positive
+        Some(if negative && !result.0.is_zero() {
+            result.neg()
+        } else {
+            result
+        })
+    }
+
+    /// Adds two numbers, returning `None` on overflow.
+    #[must_use]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let result = Self(self.0.overflowing_add(rhs.0).0);
+        (self.is_negative() != rhs.is_negative() || result.is_negative() == self.is_negative())
+            .then_some(result)
+    }
+
+    /// Multiplies two numbers, returning `None` on overflow.
+    #[must_use]
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let result = (U512::from(self.abs().0) * U512::from(other.abs().0)) >> Self::DECIMAL_BITS;
+        Self::signed_result(result, self.sign() * other.sign() < 0)
+    }
+
+    /// Divides two numbers, returning `None` on overflow or division by zero.
+    #[must_use]
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.0.is_zero() {
+            return None;
+        }
+
+        let result = (U512::from(self.abs().0) << Self::DECIMAL_BITS) / U512::from(rhs.abs().0);
+        Self::signed_result(result, self.is_negative() != rhs.is_negative())
+    }
 }
 impl Div for U256FD128 {
     type Output = Self;
@@ -607,6 +649,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_checked_operations() {
+        let two = U256FD128::from_whole(2);
+        let three = U256FD128::from_whole(3);
+        let big = U256FD128::from_whole(1u128 << 126);
+
+        assert_eq!(two.checked_add(three), Some(two + three));
+        assert_eq!(two.neg().checked_add(three), Some(two.neg() + three));
+        assert_eq!(big.checked_add(big), None);
+        assert_eq!(
+            big.neg()
+                .checked_add(big.neg())
+                .map(|sum| sum.is_negative()),
+            Some(true)
+        );
+
+        assert_eq!(two.checked_mul(three), Some(two * three));
+        assert_eq!(two.neg().checked_mul(three), Some(two.neg() * three));
+        assert_eq!(three.checked_div(two.neg()), Some(three / two.neg()));
+
+        assert_eq!(big.checked_mul(big), None);
+        assert_eq!(big.checked_mul(two), None);
+        assert_eq!(big.checked_div(U256FD128::from_whole(1) / two), None);
+        assert_eq!(two.checked_div(U256FD128::ZERO), None);
+    }
+
+    #[test]
+    fn test_checked_operations_to_zero() {
+        let two = U256FD128::from_whole(2);
+        let tiny = U256FD128::new(U256::one());
+
+        // A negative result of zero must not be negated
+        assert_eq!(
+            two.neg().checked_mul(U256FD128::ZERO),
+            Some(U256FD128::ZERO)
+        );
+        assert_eq!(
+            U256FD128::ZERO.checked_div(two.neg()),
+            Some(U256FD128::ZERO)
+        );
+        assert_eq!(tiny.neg().checked_mul(tiny), Some(U256FD128::ZERO));
+        assert_eq!(
+            tiny.neg().checked_div(U256FD128::from_whole(4)),
+            Some(U256FD128::ZERO)
+        );
+    }
+
     #[test]
     fn test_addition_overflow() {
         // Create a large positive number near the max
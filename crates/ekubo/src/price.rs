@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
-use crate::math::u256fd128::U256FD128;
+use starknet::core::types::U256 as RawU256;
+
+use crate::math::u256fd128::{U256FD128, U512};
 
 #[derive(Debug, Clone)]
 pub struct PairRatio(pub U256FD128);
@@ -10,6 +12,43 @@ impl PairRatio {
     pub fn inverse(&self) -> Self {
         PairRatio(U256FD128::from_whole(1) / self.0)
     }
+
+    /// Converts the ratio of raw amounts into a ratio of whole tokens, given the decimals of
+    /// the base token and of the quote token.
+    #[must_use]
+    pub fn to_decimal(&self, base_decimals: u8, quote_decimals: u8) -> f64 {
+        f64::from(self.0) * 10f64.powi(i32::from(base_decimals) - i32::from(quote_decimals))
+    }
+
+    /// Divides an amount by the ratio, converting an amount of the quote token into the base
+    /// token.
+    ///
+    /// Returns `None` if the ratio is not positive, or if the result does not fit in 256 bits.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // Splitting into 64 bits words
+    pub fn convert_back(&self, amount: RawU256) -> Option<RawU256> {
+        if self.0.is_negative() || self.0.raw().is_zero() {
+            return None;
+        }
+
+        let [low, high] =
+            [amount.low(), amount.high()].map(|part| [part as u64, (part >> 64) as u64]);
+        let amount = U512([low[0], low[1], high[0], high[1], 0, 0, 0, 0]);
+        let ratio = self.0.raw().0;
+        let ratio = U512([ratio[0], ratio[1], ratio[2], ratio[3], 0, 0, 0, 0]);
+
+        // The ratio has 128 decimal bits
+        let result = (amount << 128) / ratio;
+
+        if result.0[4..].iter().any(|word| *word != 0) {
+            return None;
+        }
+
+        Some(RawU256::from_words(
+            u128::from(result.0[0]) | u128::from(result.0[1]) << 64,
+            u128::from(result.0[2]) | u128::from(result.0[3]) << 64,
+        ))
+    }
 }
 
 impl std::ops::Deref for PairRatio {
@@ -25,3 +64,43 @@ impl Display for PairRatio {
         write!(f, "{}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_decimal() {
+        // 1569.14 USDC (6 decimals) for 1 ETH (18 decimals)
+        let ratio =
+            PairRatio(U256FD128::from_whole(156_914) / U256FD128::from_whole(10u128.pow(14)));
+        assert!((ratio.to_decimal(18, 6) - 1569.14).abs() < 1e-6);
+        assert!((ratio.inverse().to_decimal(6, 18) - 1.0 / 1569.14).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_convert_back() {
+        // 4 tokens for 1 base token
+        let ratio = PairRatio(U256FD128::from_whole(4));
+        assert_eq!(
+            ratio.convert_back(RawU256::from(1_000_000_000_000_000_000u128)),
+            Some(RawU256::from(250_000_000_000_000_000u128))
+        );
+
+        // Half a token for 1 base token
+        let ratio = PairRatio(U256FD128::from_whole(1) / U256FD128::from_whole(2));
+        assert_eq!(
+            ratio.convert_back(RawU256::from(3u8)),
+            Some(RawU256::from(6u8))
+        );
+
+        assert_eq!(
+            PairRatio(U256FD128::from_whole(0)).convert_back(RawU256::from(1u8)),
+            None
+        );
+
+        // Does not fit in 256 bits
+        let tiny = PairRatio(U256FD128::new(1u8.into()));
+        assert_eq!(tiny.convert_back(RawU256::from_words(0, u128::MAX)), None);
+    }
+}
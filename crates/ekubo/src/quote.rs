@@ -0,0 +1,202 @@
+//! Simulation of a swap against the state of a pool.
+
+use ekubo_sdk::math::uint::U256;
+use starknet::core::types::Felt;
+
+use crate::{
+    contract::{pool_price::PoolKey, pool_state::PoolState},
+    math::{
+        tick::{tick_to_sqrt_ratio, MAX_TICK, MIN_TICK},
+        u256fd128::U256FD128,
+    },
+};
+
+/// Outcome of a swap of an exact input amount.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    /// Amount of the input token, fee included.
+    pub amount_in: u128,
+    /// Part of the input paid as fee, rounded up.
+    pub fee: u128,
+    /// Amount of the output token received, rounded down.
+    pub amount_out: u128,
+    /// Relative difference between the spot price and the price of the swap, fee excluded.
+    pub price_impact: U256FD128,
+    /// Square root of the price of the pool after the swap.
+    pub sqrt_ratio_after: U256FD128,
+    /// Whether the price after the swap stays between the ticks around the current one that can be
+    /// initialized. Past them, the liquidity of the pool may change and the quote is only an
+    /// estimate.
+    pub within_tick_range: bool,
+}
+
+fn whole(value: u128) -> Option<U256FD128> {
+    // The sign bit must stay free
+    i128::try_from(value)
+        .is_ok()
+        .then(|| U256FD128::from_whole(value))
+}
+
+fn floor(value: U256FD128) -> Option<u128> {
+    (!value.is_negative()).then(|| (value.raw() >> 128).as_u128())
+}
+
+fn ceil(value: U256FD128) -> Option<u128> {
+    let has_decimals = !(value.raw() & ((U256::one() << 128) - 1)).is_zero();
+    floor(value)?.checked_add(u128::from(has_decimals))
+}
+
+/// Square roots of the prices bounding the range of the current tick, where no tick can be
+/// initialized, so where the liquidity cannot change.
+fn tick_range(key: &PoolKey, state: &PoolState) -> Option<(U256FD128, U256FD128)> {
+    let spacing = i32::try_from(key.tick_spacing).ok().filter(|s| *s > 0)?;
+    let tick = i32::try_from(state.tick.value).ok()?;
+    let tick = if state.tick.sign { -tick } else { tick };
+
+    // Only multiples of the tick spacing can be initialized
+    let lower = tick.saturating_sub(tick.rem_euclid(spacing));
+    let upper = lower.saturating_add(spacing);
+    Some((
+        tick_to_sqrt_ratio(lower.max(MIN_TICK))?,
+        tick_to_sqrt_ratio(upper.min(MAX_TICK))?,
+    ))
+}
+
+/// Quotes a swap of `amount_in` of `token_in` in the pool.
+///
+/// Quotes assume that the liquidity of the current tick range is constant for the whole swap. It
+/// only holds while the price stays within the tick spacing around the current tick, which
+/// [`Quote::within_tick_range`] tells: past it, the price impact may be underestimated.
+///
+/// Returns `None` if the token is not in the pool, if the pool has no liquidity or if the swap
+/// overflows.
+#[must_use]
+pub fn quote(key: &PoolKey, state: &PoolState, token_in: Felt, amount_in: u128) -> Option<Quote> {
+    if state.liquidity == 0 || (token_in != key.token0 && token_in != key.token1) {
+        return None;
+    }
+
+    let one = U256FD128::from_whole(1);
+    let liquidity = whole(state.liquidity)?;
+    let sqrt_ratio = U256FD128::from(state.sqrt_ratio);
+    if sqrt_ratio.raw().is_zero() {
+        return None;
+    }
+
+    // The fee is a 0.128 fixed point number, taken from the input
+    let fee = ceil(whole(amount_in)?.checked_mul(U256FD128::new(U256::from(key.fee)))?)?;
+    let amount = whole(amount_in.checked_sub(fee)?)?;
+
+    let (amount_out, sqrt_ratio_after, price_impact) = if token_in == key.token0 {
+        // Selling token0 lowers the price: 1 / sqrt_after = 1 / sqrt + amount / liquidity
+        let sqrt_ratio_after = one.checked_div(
+            one.checked_div(sqrt_ratio)?
+                .checked_add(amount.checked_div(liquidity)?)?,
+        )?;
+        let amount_out = amount.checked_mul(sqrt_ratio.checked_mul(sqrt_ratio_after)?)?;
+        let price_impact = one - sqrt_ratio_after.checked_div(sqrt_ratio)?;
+        (amount_out, sqrt_ratio_after, price_impact)
+    } else {
+        // Selling token1 raises the price: sqrt_after = sqrt + amount / liquidity
+        let sqrt_ratio_after = sqrt_ratio.checked_add(amount.checked_div(liquidity)?)?;
+        let amount_out = amount
+            .checked_div(sqrt_ratio)?
+            .checked_div(sqrt_ratio_after)?;
+        let price_impact = one - sqrt_ratio.checked_div(sqrt_ratio_after)?;
+        (amount_out, sqrt_ratio_after, price_impact)
+    };
+
+    let (lower, upper) = tick_range(key, state)?;
+
+    Some(Quote {
+        amount_in,
+        fee,
+        amount_out: floor(amount_out)?,
+        price_impact,
+        sqrt_ratio_after,
+        within_tick_range: (lower..=upper).contains(&sqrt_ratio_after),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::pool_price::I129;
+    use starknet::core::types::U256 as RawU256;
+
+    fn pool(fee: u128) -> PoolKey {
+        pool_with_spacing(fee, 1)
+    }
+
+    fn pool_with_spacing(fee: u128, tick_spacing: u32) -> PoolKey {
+        PoolKey {
+            token0: Felt::ONE,
+            token1: Felt::TWO,
+            fee,
+            tick_spacing,
+            extension: Felt::ZERO,
+        }
+    }
+
+    fn state(liquidity: u128) -> PoolState {
+        // A price of 1
+        PoolState {
+            sqrt_ratio: RawU256::from_words(0, 1),
+            tick: I129 {
+                value: 0,
+                sign: false,
+            },
+            liquidity,
+        }
+    }
+
+    #[test]
+    fn test_quote() {
+        let half = U256FD128::from_whole(1) / U256FD128::from_whole(2);
+
+        let sell_token0 = quote(&pool(0), &state(1000), Felt::ONE, 1000).unwrap();
+        assert_eq!(sell_token0.fee, 0);
+        assert_eq!(sell_token0.amount_out, 500);
+        assert_eq!(sell_token0.price_impact, half);
+        assert_eq!(sell_token0.sqrt_ratio_after, half);
+        assert!(!sell_token0.within_tick_range);
+
+        let sell_token1 = quote(&pool(0), &state(1000), Felt::TWO, 1000).unwrap();
+        assert_eq!(sell_token1.amount_out, 500);
+        assert_eq!(sell_token1.price_impact, half);
+        assert_eq!(sell_token1.sqrt_ratio_after, U256FD128::from_whole(2));
+
+        // Half of the input goes to fees
+        let with_fee = quote(&pool(1 << 127), &state(1000), Felt::ONE, 2000).unwrap();
+        assert_eq!(with_fee.fee, 1000);
+        assert_eq!(with_fee.amount_out, 500);
+
+        // A deeper pool has less impact
+        let deep = quote(&pool(0), &state(1_000_000), Felt::ONE, 1000).unwrap();
+        assert_eq!(deep.amount_out, 999);
+        assert!(deep.price_impact < sell_token0.price_impact);
+    }
+
+    #[test]
+    fn test_quote_tick_range() {
+        // Up to 1000 ticks above the current one, which is 1.0005 as a square root price
+        let key = pool_with_spacing(0, 1000);
+
+        let small = quote(&key, &state(1_000_000), Felt::TWO, 100).unwrap();
+        assert!(small.within_tick_range);
+
+        let large = quote(&key, &state(1_000_000), Felt::TWO, 1000).unwrap();
+        assert!(!large.within_tick_range);
+
+        // The current tick is the lower bound of its range, any sale of token0 leaves it
+        let sell_token0 = quote(&key, &state(1_000_000), Felt::ONE, 100).unwrap();
+        assert!(!sell_token0.within_tick_range);
+    }
+
+    #[test]
+    fn test_quote_invalid() {
+        assert!(quote(&pool(0), &state(0), Felt::ONE, 1000).is_none());
+        assert!(quote(&pool(0), &state(1000), Felt::THREE, 1000).is_none());
+        assert!(quote(&pool(0), &state(1000), Felt::ONE, u128::MAX).is_none());
+    }
+}
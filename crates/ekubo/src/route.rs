@@ -0,0 +1,114 @@
+//! Pricing of a token pair through intermediate tokens, for pairs without a direct pool.
+
+use serde::Serialize;
+use starknet::core::types::Felt;
+
+use crate::{contract::pool_price::PoolKey, price::PairRatio};
+
+/// A swap of `from` into `to` through `pool`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hop {
+    pub from: Felt,
+    pub to: Felt,
+    pub pool: PoolKey,
+}
+
+impl Hop {
+    /// Converts the price of the pool, the amount of `token1` for one `token0`, into the amount
+    /// of `to` for one `from`.
+    ///
+    /// Returns `None` if the price is not positive, as it cannot be inverted.
+    #[must_use]
+    pub fn orient(&self, price: PairRatio) -> Option<PairRatio> {
+        if price.is_negative() || price.raw().is_zero() {
+            return None;
+        }
+
+        Some(if self.from < self.to {
+            price
+        } else {
+            price.inverse()
+        })
+    }
+}
+
+/// A path between two tokens, and the resulting price.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub hops: Vec<Hop>,
+    /// Amount of the last token of the route for one of the first token.
+    pub ratio: PairRatio,
+    /// Total value locked of the thinnest pool of the route, in the first token.
+    pub liquidity: f64,
+}
+
+/// Lists the token paths from `base` to `quote` to try, shortest first.
+///
+/// Intermediates are tried in the given order, `base`, `quote` and duplicates are ignored.
+#[must_use]
+pub fn candidate_paths(base: Felt, quote: Felt, intermediates: &[Felt]) -> Vec<Vec<Felt>> {
+    let mut paths = vec![vec![base, quote]];
+
+    for intermediate in intermediates {
+        if *intermediate == base || *intermediate == quote {
+            continue;
+        }
+
+        let path = vec![base, *intermediate, quote];
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::u256fd128::U256FD128;
+
+    fn hop(from: u8, to: u8) -> Hop {
+        let (token0, token1) = if from < to { (from, to) } else { (to, from) };
+
+        Hop {
+            from: Felt::from(from),
+            to: Felt::from(to),
+            pool: PoolKey {
+                token0: Felt::from(token0),
+                token1: Felt::from(token1),
+                fee: 0,
+                tick_spacing: 1,
+                extension: Felt::ZERO,
+            },
+        }
+    }
+
+    #[test]
+    fn test_candidate_paths() {
+        let [a, b, c, d] = [1u8, 2, 3, 4].map(Felt::from);
+
+        assert_eq!(
+            candidate_paths(a, b, &[c, a, b, d, c]),
+            vec![vec![a, b], vec![a, c, b], vec![a, d, b]]
+        );
+        assert_eq!(candidate_paths(a, b, &[]), vec![vec![a, b]]);
+    }
+
+    #[test]
+    fn test_orient() {
+        let price = PairRatio(U256FD128::from_whole(4));
+
+        assert_eq!(
+            hop(1, 2).orient(price.clone()).unwrap().0,
+            U256FD128::from_whole(4)
+        );
+        assert_eq!(
+            hop(2, 1).orient(price).unwrap().0,
+            U256FD128::from_whole(1) / U256FD128::from_whole(4)
+        );
+        assert!(hop(2, 1)
+            .orient(PairRatio(U256FD128::from_whole(0)))
+            .is_none());
+    }
+}
@@ -0,0 +1,178 @@
+//! Ranking of the pools of a pair, so that thin pools cannot set the price of a token.
+//!
+//! Scores only order pools, so they are computed with floats rather than fixed point numbers.
+
+use starknet::core::types::Felt;
+
+use crate::{api::pool::Pool, price::PairRatio};
+
+/// How the pools of a pair are ranked.
+#[derive(Debug, Clone)]
+pub struct ScoringStrategy {
+    /// Weight of the 24h volume against the total value locked.
+    pub volume_weight: f64,
+    /// Minimum total value locked of a pool, in the first token of the route.
+    pub min_liquidity: f64,
+}
+
+impl Default for ScoringStrategy {
+    fn default() -> Self {
+        Self {
+            volume_weight: 1.0,
+            min_liquidity: 0.0,
+        }
+    }
+}
+
+/// Liquidity of a pool, in the first token of the route.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolScore {
+    /// Total value locked of both tokens, estimated from the first one.
+    pub liquidity: f64,
+    /// Volume of the first token in the last 24 hours.
+    pub volume: f64,
+    pub score: f64,
+}
+
+fn felt_to_f64(value: Felt) -> f64 {
+    value
+        .to_bytes_be()
+        .iter()
+        .fold(0.0, |acc, byte| acc * 256.0 + f64::from(*byte))
+}
+
+impl ScoringStrategy {
+    /// Scores a pool swapping `from` into its other token.
+    ///
+    /// `from_per_base` is the amount of `from` for one token of the start of the route. Only the
+    /// `from` side of the pool is valued: the price of the pool itself is not trusted, as a thin
+    /// pool can be skewed to any price. Liquidity is twice the `from` side, like a balanced pool.
+    #[must_use]
+    pub fn score(&self, pool: &Pool, from: Felt, from_per_base: &PairRatio) -> PoolScore {
+        let from_per_base = f64::from(from_per_base.0);
+        let in_base = |amount0: Felt, amount1: Felt| {
+            let from_amount = if pool.key.token0 == from {
+                amount0
+            } else {
+                amount1
+            };
+            felt_to_f64(from_amount) / from_per_base
+        };
+
+        let liquidity = 2.0 * in_base(pool.tvl0_total, pool.tvl1_total);
+        let volume = in_base(pool.volume0_24h, pool.volume1_24h);
+        // The fee is a 0.128 fixed point number
+        #[allow(clippy::cast_precision_loss)]
+        let fee = pool.key.fee as f64 / 2.0f64.powi(128);
+
+        PoolScore {
+            liquidity,
+            volume,
+            score: (liquidity + self.volume_weight * volume) * (1.0 - fee),
+        }
+    }
+
+    /// Picks the item with the best score, among the ones with enough liquidity.
+    #[must_use]
+    pub fn best<T>(&self, scored: Vec<(T, PoolScore)>) -> Option<(T, PoolScore)> {
+        scored
+            .into_iter()
+            .filter(|(_, score)| score.liquidity >= self.min_liquidity)
+            .max_by(|(_, a), (_, b)| a.score.total_cmp(&b.score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contract::pool_price::PoolKey, math::u256fd128::U256FD128};
+
+    fn pool(fee: u128, tvl0: u64, tvl1: u64, volume0: u64) -> Pool {
+        Pool {
+            key: PoolKey {
+                token0: Felt::ONE,
+                token1: Felt::TWO,
+                fee,
+                tick_spacing: 1,
+                extension: Felt::ZERO,
+            },
+            tvl0_total: tvl0.into(),
+            tvl1_total: tvl1.into(),
+            fees0_24h: Felt::ZERO,
+            fees1_24h: Felt::ZERO,
+            tvl0_delta_24h: Felt::ZERO,
+            tvl1_delta_24h: Felt::ZERO,
+            volume0_24h: volume0.into(),
+            volume1_24h: Felt::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        let strategy = ScoringStrategy::default();
+        let one = PairRatio(U256FD128::from_whole(1));
+        // 4 of token 2 for one token 1
+        let price = PairRatio(U256FD128::from_whole(4));
+
+        let score = strategy.score(&pool(0, 100, 400, 10), Felt::ONE, &one);
+        assert!((score.liquidity - 200.0).abs() < 1e-9);
+        assert!((score.volume - 10.0).abs() < 1e-9);
+        assert!((score.score - 210.0).abs() < 1e-9);
+
+        // Swapping token 2 into token 1 after a first hop from token 1
+        let score = strategy.score(&pool(0, 100, 400, 10), Felt::TWO, &price);
+        assert!((score.liquidity - 200.0).abs() < 1e-9);
+
+        // Half of the value goes to fees
+        let score = strategy.score(&pool(1 << 127, 100, 400, 0), Felt::ONE, &one);
+        assert!((score.score - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_skewed_pool() {
+        let strategy = ScoringStrategy {
+            volume_weight: 1.0,
+            min_liquidity: 50.0,
+        };
+        let one = PairRatio(U256FD128::from_whole(1));
+
+        // A tiny pool pushed to an absurd price holds a lot of the other token, but almost none
+        // of the priced one
+        let skewed = strategy.score(&pool(0, 1, 1_000_000_000, 0), Felt::ONE, &one);
+        assert!((skewed.liquidity - 2.0).abs() < 1e-9);
+        let deep = strategy.score(&pool(0, 100, 400, 0), Felt::ONE, &one);
+
+        assert_eq!(
+            strategy
+                .best(vec![("skewed", skewed), ("deep", deep)])
+                .map(|(name, _)| name),
+            Some("deep")
+        );
+        assert!(strategy.best(vec![("skewed", skewed)]).is_none());
+    }
+
+    #[test]
+    fn test_best() {
+        let strategy = ScoringStrategy {
+            volume_weight: 1.0,
+            min_liquidity: 50.0,
+        };
+        let score = |liquidity: f64, score: f64| PoolScore {
+            liquidity,
+            volume: 0.0,
+            score,
+        };
+
+        assert_eq!(
+            strategy
+                .best(vec![
+                    ("thin", score(10.0, 1000.0)),
+                    ("deep", score(100.0, 100.0)),
+                    ("deeper", score(200.0, 200.0)),
+                ])
+                .map(|(name, _)| name),
+            Some("deeper")
+        );
+        assert!(strategy.best(vec![("thin", score(10.0, 1000.0))]).is_none());
+    }
+}
@@ -0,0 +1,275 @@
+//! Mock of a Starknet node and of the Ekubo API, replaying recorded responses so the client can
+//! be tested without network.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use mockito::{Mock, ServerGuard};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use starknet::{
+    core::{
+        types::{Felt, FunctionCall},
+        utils::get_selector_from_name,
+    },
+    providers::{
+        jsonrpc::{JsonRpcMethod, JsonRpcResponse, JsonRpcTransport},
+        JsonRpcClient, ProviderRequestData,
+    },
+};
+
+/// Core contract of Ekubo on mainnet.
+pub(crate) const CORE: Felt =
+    Felt::from_hex_unchecked("0x00000005dd3d2f4429af886cd1a3b08289dbcea99a294197e9eb43b0e0325b4b");
+pub(crate) const ETH: Felt =
+    Felt::from_hex_unchecked("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+pub(crate) const USDC: Felt =
+    Felt::from_hex_unchecked("0x053c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8");
+pub(crate) const STRK: Felt =
+    Felt::from_hex_unchecked("0x04718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d");
+
+/// Pools of the Ekubo API for each pair of tokens, as recorded.
+const API_FIXTURES: [(Felt, Felt, &str); 3] = [
+    (
+        ETH,
+        USDC,
+        include_str!("../fixtures/api_eth_usdc_pools.json"),
+    ),
+    (
+        STRK,
+        ETH,
+        include_str!("../fixtures/api_strk_eth_pools.json"),
+    ),
+    (
+        STRK,
+        USDC,
+        include_str!("../fixtures/api_strk_usdc_pools.json"),
+    ),
+];
+
+/// Calls to the contracts, as recorded.
+const RPC_FIXTURE: &str = include_str!("../fixtures/rpc_pools.json");
+
+#[derive(Deserialize)]
+struct RecordedCall {
+    contract_address: Felt,
+    entry_point: String,
+    calldata: Vec<Felt>,
+    result: Vec<Felt>,
+}
+
+#[derive(Deserialize)]
+struct RpcFixture {
+    block_number: u64,
+    calls: Vec<RecordedCall>,
+}
+
+/// A node answering the recorded calls, at a block that tests can move.
+#[derive(Debug)]
+pub(crate) struct MockNode {
+    block_number: AtomicU64,
+    calls: HashMap<(Felt, Felt, Vec<Felt>), Vec<Felt>>,
+    requests: AtomicUsize,
+    batches: AtomicUsize,
+    call_blocks: Mutex<Vec<Value>>,
+}
+
+impl MockNode {
+    /// Creates a node answering the calls of the recorded fixture.
+    pub(crate) fn new() -> Arc<Self> {
+        let fixture: RpcFixture =
+            serde_json::from_str(RPC_FIXTURE).expect("the RPC fixture is valid");
+        let calls = fixture
+            .calls
+            .into_iter()
+            .map(|call| {
+                let selector =
+                    get_selector_from_name(&call.entry_point).expect("valid entry point name");
+                (
+                    (call.contract_address, selector, call.calldata),
+                    call.result,
+                )
+            })
+            .collect();
+
+        Arc::new(Self {
+            block_number: AtomicU64::new(fixture.block_number),
+            calls,
+            requests: AtomicUsize::new(0),
+            batches: AtomicUsize::new(0),
+            call_blocks: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// A provider sending its requests to this node.
+    pub(crate) fn provider(self: &Arc<Self>) -> JsonRpcClient<MockTransport> {
+        JsonRpcClient::new(MockTransport(Arc::clone(self)))
+    }
+
+    pub(crate) fn block_number(&self) -> u64 {
+        self.block_number.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_block_number(&self, block_number: u64) {
+        self.block_number.store(block_number, Ordering::SeqCst);
+    }
+
+    /// Number of requests received, a batch counting once per request in it.
+    pub(crate) fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Number of batches received.
+    pub(crate) fn batches(&self) -> usize {
+        self.batches.load(Ordering::SeqCst)
+    }
+
+    /// Blocks of the calls received, as sent by the provider.
+    pub(crate) fn call_blocks(&self) -> Vec<Value> {
+        self.call_blocks.lock().unwrap().clone()
+    }
+
+    fn call(&self, call: &FunctionCall) -> Value {
+        let key = (
+            call.contract_address,
+            call.entry_point_selector,
+            call.calldata.clone(),
+        );
+        match self.calls.get(&key) {
+            Some(result) => json!({ "result": result }),
+            None => json!({ "error": { "code": -32603, "message": "No recorded response" } }),
+        }
+    }
+
+    /// Answers a request, as the body of a JSON-RPC response without its id.
+    fn answer(&self, method: &str, params: &Value) -> Value {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+
+        match method {
+            "starknet_blockNumber" => json!({ "result": self.block_number() }),
+            "starknet_call" => {
+                // Parameters are either named or positional
+                let request = params.get("request").or_else(|| params.get(0));
+                let block_id = params.get("block_id").or_else(|| params.get(1));
+                self.call_blocks
+                    .lock()
+                    .unwrap()
+                    .push(block_id.cloned().unwrap_or_default());
+                match request.and_then(|request| FunctionCall::deserialize(request).ok()) {
+                    Some(call) => self.call(&call),
+                    None => json!({ "error": { "code": -32602, "message": "Invalid params" } }),
+                }
+            }
+            _ => json!({ "error": { "code": -32601, "message": "Method not found" } }),
+        }
+    }
+}
+
+/// Transport of a provider to a [`MockNode`].
+#[derive(Debug, Clone)]
+pub(crate) struct MockTransport(Arc<MockNode>);
+
+fn response<R: DeserializeOwned>(id: usize, mut body: Value) -> serde_json::Result<R> {
+    body["id"] = id.into();
+    body["jsonrpc"] = "2.0".into();
+    serde_json::from_value(body)
+}
+
+fn method_name(method: &impl Serialize) -> String {
+    serde_json::to_value(method)
+        .ok()
+        .and_then(|method| method.as_str().map(ToString::to_string))
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl JsonRpcTransport for MockTransport {
+    type Error = serde_json::Error;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let body = self
+            .0
+            .answer(&method_name(&method), &serde_json::to_value(params)?);
+        response(1, body)
+    }
+
+    async fn send_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<JsonRpcResponse<Value>>, Self::Error>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        self.0.batches.fetch_add(1, Ordering::SeqCst);
+        requests
+            .as_ref()
+            .iter()
+            .enumerate()
+            .map(|(id, request)| {
+                let body = match request {
+                    ProviderRequestData::BlockNumber(_) => {
+                        self.0.answer("starknet_blockNumber", &Value::Null)
+                    }
+                    ProviderRequestData::Call(call) => self.0.answer(
+                        "starknet_call",
+                        &json!({ "request": call.request, "block_id": call.block_id }),
+                    ),
+                    _ => self.0.answer("", &Value::Null),
+                };
+                response(id, body)
+            })
+            .collect()
+    }
+}
+
+/// Ekubo API answering the recorded pools of each pair.
+pub(crate) struct MockApi {
+    server: ServerGuard,
+    _mocks: Vec<Mock>,
+}
+
+impl MockApi {
+    pub(crate) async fn new() -> Self {
+        let mut server = mockito::Server::new_async().await;
+
+        let mut mocks = Vec::with_capacity(API_FIXTURES.len());
+        for (token0, token1, body) in API_FIXTURES {
+            let path = format!(
+                "/pair/{}/{}/pools",
+                token0.to_fixed_hex_string(),
+                token1.to_fixed_hex_string()
+            );
+            let mock = server
+                .mock("GET", &*path)
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(body)
+                .create_async()
+                .await;
+            mocks.push(mock);
+        }
+
+        Self {
+            server,
+            _mocks: mocks,
+        }
+    }
+
+    pub(crate) fn url(&self) -> String {
+        self.server.url()
+    }
+}
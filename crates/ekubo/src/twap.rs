@@ -0,0 +1,74 @@
+//! Time weighted average price, which a single swap right before a sample cannot move much.
+
+use crate::{math::u256fd128::U256FD128, price::PairRatio};
+
+/// Computes the time weighted average of prices between `from` and `to`, as unix timestamps.
+///
+/// `samples` are sorted by time, and each price holds until the next one. Prices before `from`
+/// only count from `from`, so the last sample before the window should be included. When the
+/// first sample is after `from`, the average starts at that sample.
+///
+/// Returns `None` if there is no sample at or before `to`.
+#[must_use]
+pub fn twap(samples: &[(i64, PairRatio)], from: i64, to: i64) -> Option<PairRatio> {
+    let samples = &samples[..samples.partition_point(|(at, _)| *at <= to)];
+    let (_, last) = samples.last()?;
+
+    let start = samples[0].0.max(from);
+    let duration = to - start;
+    if duration <= 0 {
+        return Some(last.clone());
+    }
+    let duration = U256FD128::from_whole(duration.unsigned_abs().into());
+
+    let mut average = U256FD128::ZERO;
+    for (i, (at, ratio)) in samples.iter().enumerate() {
+        let end = samples.get(i + 1).map_or(to, |(next, _)| *next);
+        let held = end - (*at).max(start);
+        if held <= 0 {
+            continue;
+        }
+
+        // Weighting first keeps every term below the largest price
+        let weight = U256FD128::from_whole(held.unsigned_abs().into()) / duration;
+        average = average + ratio.0 * weight;
+    }
+
+    Some(PairRatio(average))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(value: u128) -> PairRatio {
+        PairRatio(U256FD128::from_whole(value))
+    }
+
+    fn is_close(a: &PairRatio, b: u128) -> bool {
+        (f64::from(a.0) - f64::from(U256FD128::from_whole(b))).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_twap() {
+        let samples = [
+            (0, ratio(10)),
+            (30, ratio(30)),
+            (60, ratio(20)),
+            (90, ratio(5)),
+        ];
+
+        // 30s at 30, then 30s at 20
+        assert!(is_close(&twap(&samples, 30, 90).unwrap(), 25));
+        // The price before the window holds for its first 15s
+        assert!(is_close(&twap(&samples, 45, 75).unwrap(), 25));
+        // A sample at the end of the window has no weight yet
+        assert!(is_close(&twap(&samples, 0, 90).unwrap(), 20));
+        // The window starts at the first sample
+        assert!(is_close(&twap(&samples[1..], 0, 90).unwrap(), 25));
+
+        assert!(is_close(&twap(&samples, 90, 90).unwrap(), 5));
+        assert!(twap(&samples, -20, -10).is_none());
+        assert!(twap(&[], 0, 10).is_none());
+    }
+}
@@ -20,6 +20,7 @@ pub struct Conf {
     #[config(nested)]
     pub monitoring: Monitoring,
 
+    /// Tokens stored on the first start, the list is then managed through the admin API.
     #[config(default = [])]
     pub token: Vec<Token>,
 
@@ -38,6 +39,9 @@ pub struct Conf {
     #[config(nested)]
     pub gg_xyz: GgXyzConfig,
 
+    #[config(nested)]
+    pub admin: AdminConfig,
+
     pub default_token: String,
 }
 
@@ -45,6 +49,10 @@ pub struct Conf {
 pub struct Token {
     pub symbol: String,
     pub address: Felt,
+    /// Read from the contract when not configured.
+    pub name: Option<String>,
+    /// Read from the contract when not configured.
+    pub decimals: Option<u8>,
 }
 
 #[derive(Config, Debug, Clone)]
@@ -53,6 +61,9 @@ pub struct ToriiConfig {
     pub world_address: Felt,
     #[config(env = "TORII_URL")]
     pub torii_url: Url,
+    /// Whether to use the gRPC subscription of torii for live updates, on top of polling.
+    #[config(default = true, env = "TORII_LIVE_UPDATES")]
+    pub live_updates: bool,
 }
 
 #[derive(Config, Debug, Clone)]
@@ -67,6 +78,17 @@ pub struct EkuboConfig {
     pub api_url: Url,
     #[config(env = "EKUBO_CORE_CONTRACT_ADDRESS")]
     pub core_contract_address: Felt,
+    /// Tokens to route through when a token has no pool against the main token, on top of the
+    /// configured tokens.
+    #[config(default = [])]
+    pub routing_tokens: Vec<Felt>,
+    /// Minimum total value locked of a pool for it to price a token, in the smallest unit of the
+    /// main token.
+    #[config(default = 0.0, env = "EKUBO_MIN_LIQUIDITY")]
+    pub min_liquidity: f64,
+    /// Window of the time weighted average prices, in seconds.
+    #[config(default = 1800, env = "EKUBO_TWAP_WINDOW")]
+    pub twap_window: u64,
 }
 
 #[derive(Config, Debug, Clone)]
@@ -85,6 +107,13 @@ pub struct GgXyzConfig {
     pub api_key: String,
 }
 
+#[derive(Config, Debug, Clone)]
+pub struct AdminConfig {
+    /// Key to send as a bearer token to the admin API, which is disabled when not set.
+    #[config(env = "ADMIN_API_KEY")]
+    pub api_key: Option<String>,
+}
+
 #[derive(Config, Debug, Clone)]
 pub struct Monitoring {
     /// Whether monitoring is enabled or not
@@ -9,16 +9,27 @@ use axum::{
     routing::get,
     Json, Router,
 };
-use chaindata_repository::LandRepository;
+use chaindata_repository::{
+    AuctionRepository, EventRepository, LandRepository, LandStakeRepository, LeaderboardRepository,
+    TokenPriceRepository, TokenRepository,
+};
 use chaindata_service::{ChainDataService, ChainDataServiceConfiguration};
 use config::Conf;
 use confique::Config;
 use migrations::MIGRATOR;
 use monitoring::listen_monitoring;
-use routes::{lands::LandsRoute, price::PriceRoute, tokens::TokenRoute};
+use routes::{
+    admin::AdminRoute, auctions::AuctionsRoute, events::EventsRoute, health::HealthRoute,
+    lands::LandsRoute, leaderboards::LeaderboardsRoute, players::PlayersRoute, price::PriceRoute,
+    tokens::TokenRoute,
+};
 use serde::{Deserialize, Serialize};
-use service::{ekubo::EkuboService, token::TokenService};
+use service::{
+    ekubo::EkuboService, leaderboard::LeaderboardService, token::TokenService,
+    valuation::ValuationService,
+};
 use sqlx::{postgres::PgConnectOptions, ConnectOptions, PgPool};
+use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient};
 use state::AppState;
 use tokio::{
     select,
@@ -69,14 +80,6 @@ async fn main() -> Result<()> {
 
     let monitor = MonitorManager::new();
 
-    let token_service = Arc::new(
-        TokenService::new(&config).with_context(|| "Error while setting up token service")?,
-    );
-
-    let ekubo = EkuboService::new(&config, token_service.clone(), &monitor)
-        .await
-        .with_context(|| "Error while setting up ekubo config")?;
-
     let options = PgConnectOptions::from_url(&config.database.url)
         .with_context(|| "Error while setting up database connection")?
         .application_name("ponzidexer");
@@ -91,11 +94,31 @@ async fn main() -> Result<()> {
         .await
         .with_context(|| "Error while migrating database")?;
 
+    let token_price_repository = Arc::new(TokenPriceRepository::new(pool.clone()));
+    let token_repository = Arc::new(TokenRepository::new(pool.clone()));
+
+    let rpc_client = JsonRpcClient::new(HttpTransport::new(config.starknet.rpc_url.clone()));
+    let token_service = Arc::new(
+        TokenService::new(&config, token_repository, rpc_client)
+            .await
+            .with_context(|| "Error while setting up token service")?,
+    );
+
+    let ekubo = EkuboService::new(
+        &config,
+        token_service.clone(),
+        token_price_repository.clone(),
+        &monitor,
+    )
+    .await
+    .with_context(|| "Error while setting up ekubo config")?;
+
     let chaindata_service = ChainDataService::new(
         pool.clone(),
         ChainDataServiceConfiguration {
             torii_url: config.torii.torii_url.clone().into(),
             world_address: config.torii.world_address,
+            torii_live_updates: config.torii.live_updates,
             gg_xyz_enabled: config.gg_xyz.enabled,
             gg_xyz_api_url: config.gg_xyz.api_url.clone(),
             gg_xyz_api_key: config.gg_xyz.api_key.clone(),
@@ -108,11 +131,38 @@ async fn main() -> Result<()> {
     chaindata_service.start();
 
     let land_repository = Arc::new(LandRepository::new(pool.clone()));
+    let land_stake_repository = Arc::new(LandStakeRepository::new(pool.clone()));
+    let auction_repository = Arc::new(AuctionRepository::new(pool.clone()));
+    let event_repository = Arc::new(EventRepository::new(pool.clone()));
+    let leaderboard_repository = Arc::new(LeaderboardRepository::new(pool.clone()));
+
+    let valuation_service = Arc::new(ValuationService::new(
+        token_service.clone(),
+        token_price_repository.clone(),
+    ));
+
+    // Scheduled to update the leaderboards, which are then served from the database
+    LeaderboardService::new(
+        land_repository.clone(),
+        land_stake_repository.clone(),
+        leaderboard_repository.clone(),
+        ekubo.clone(),
+        valuation_service.clone(),
+        &monitor,
+    )
+    .with_context(|| "Error while setting up leaderboard service")?;
 
     let app_state = AppState {
         token_service: token_service.clone(),
         ekubo_service: ekubo.clone(),
+        valuation_service,
         land_repository,
+        land_stake_repository,
+        auction_repository,
+        event_repository,
+        leaderboard_repository,
+        token_price_repository,
+        chaindata_service: chaindata_service.clone(),
     };
 
     let cors = CorsLayer::new()
@@ -144,8 +194,46 @@ async fn main() -> Result<()> {
             "/lands",
             LandsRoute::new().router().with_state(app_state.clone()),
         )
+        .nest(
+            "/auctions",
+            AuctionsRoute::new().router().with_state(app_state.clone()),
+        )
+        .nest(
+            "/events",
+            EventsRoute::new().router().with_state(app_state.clone()),
+        )
+        .nest(
+            "/leaderboards",
+            LeaderboardsRoute::new()
+                .router()
+                .with_state(app_state.clone()),
+        )
+        .nest(
+            "/players",
+            PlayersRoute::new().router().with_state(app_state.clone()),
+        )
+        .merge(HealthRoute::new().router().with_state(app_state.clone()))
         // `GET /` goes to `root`
-        .route("/", get(root))
+        .route("/", get(root));
+
+    let app = if let Some(api_key) = config
+        .admin
+        .api_key
+        .as_deref()
+        .filter(|key| !key.is_empty())
+    {
+        app.nest(
+            "/admin",
+            AdminRoute::new(api_key)
+                .router()
+                .with_state(app_state.clone()),
+        )
+    } else {
+        info!("No admin API key configured, the admin API is disabled");
+        app
+    };
+
+    let app = app
         .layer(cors)
         .layer(middleware::from_fn(crate::monitoring::axum::track_metrics));
 
@@ -0,0 +1,153 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    routing::{delete, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use starknet::core::types::Felt;
+use tracing::error;
+
+use crate::{
+    config,
+    routes::tokens::Token,
+    service::{
+        ekubo::EkuboService,
+        token::{TokenError, TokenService},
+    },
+    state::AppState,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewToken {
+    pub symbol: String,
+    pub address: String,
+    /// Read from the contract when not given.
+    pub name: Option<String>,
+    /// Read from the contract when not given.
+    pub decimals: Option<u8>,
+}
+
+/// Operations reserved to the operators, authenticated by the configured key.
+pub struct AdminRoute {
+    api_key: Arc<str>,
+}
+
+impl AdminRoute {
+    #[must_use]
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.into(),
+        }
+    }
+
+    pub fn router(self) -> Router<AppState> {
+        Router::new()
+            .route("/tokens", post(Self::add_token))
+            .route("/tokens/{address}", delete(Self::remove_token))
+            .layer(middleware::from_fn_with_state(self.api_key, authenticate))
+    }
+
+    async fn add_token(
+        State(token_service): State<Arc<TokenService>>,
+        State(ekubo_service): State<Arc<EkuboService>>,
+        Json(new_token): Json<NewToken>,
+    ) -> Result<Json<Token>, StatusCode> {
+        let address = Felt::from_str(&new_token.address).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+        let token = token_service
+            .add(config::Token {
+                symbol: new_token.symbol,
+                address,
+                name: new_token.name,
+                decimals: new_token.decimals,
+            })
+            .await
+            .map_err(status)?;
+
+        // Price the new token without waiting for the next update
+        ekubo_service.refresh();
+
+        Ok(Json(token.into()))
+    }
+
+    async fn remove_token(
+        State(token_service): State<Arc<TokenService>>,
+        State(ekubo_service): State<Arc<EkuboService>>,
+        Path(address): Path<String>,
+    ) -> Result<StatusCode, StatusCode> {
+        let address = Felt::from_str(&address).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+        token_service.remove(address).await.map_err(status)?;
+        ekubo_service.forget(address).await;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+fn status(err: TokenError) -> StatusCode {
+    match err {
+        TokenError::NotFound => StatusCode::NOT_FOUND,
+        TokenError::MainToken => StatusCode::CONFLICT,
+        TokenError::Storage(err) => {
+            error!("Error while changing the tokens: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+async fn authenticate(
+    State(api_key): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if is_authorized(request.headers(), &api_key) {
+        Ok(next.run(request).await)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Whether the request carries the key as a bearer token.
+fn is_authorized(headers: &HeaderMap, api_key: &str) -> bool {
+    let Some(provided) = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+
+    // Compares every byte, so the time taken does not tell how much of the key matched
+    provided.len() == api_key.len()
+        && provided
+            .bytes()
+            .zip(api_key.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_is_authorized() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        assert!(is_authorized(&headers, "secret"));
+        assert!(!is_authorized(&headers, "secret2"));
+        assert!(!is_authorized(&headers, "other!"));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("secret"));
+        assert!(!is_authorized(&headers, "secret"));
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chaindata_models::{
+    models::AuctionModel,
+    shared::{Location, U256},
+};
+use chaindata_repository::AuctionRepository;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use ponziland_models::models::Auction;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::state::AppState;
+
+/// Maximum number of minutes that can be projected in a single request.
+const MAX_PROJECTION_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PricePoint {
+    pub at: NaiveDateTime,
+    pub price: U256,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuctionPrice {
+    pub location: Location,
+    pub start_time: NaiveDateTime,
+    pub start_price: U256,
+    pub floor_price: U256,
+    pub decay_rate: u16,
+    pub is_finished: bool,
+    pub sold_at_price: Option<U256>,
+    /// Price at which the auction can be bought right now, `None` if the auction is finished.
+    pub current_price: Option<U256>,
+    /// Price of the auction for each of the next requested minutes.
+    pub projection: Vec<PricePoint>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProjectionQuery {
+    pub minutes: Option<u32>,
+}
+
+impl AuctionPrice {
+    #[allow(clippy::cast_sign_loss)] // We are not going back in time
+    fn at(model: &AuctionModel, now: DateTime<Utc>, minutes: u32) -> Self {
+        let auction = Auction::from(model);
+        let timestamp = now.timestamp() as u64;
+
+        let (current_price, projection) = if auction.is_finished {
+            (None, Vec::new())
+        } else {
+            let projection = (1..=minutes.min(MAX_PROJECTION_MINUTES))
+                .map(|minute| PricePoint {
+                    at: (now + Duration::minutes(minute.into())).naive_utc(),
+                    price: auction
+                        .get_current_price_decay_rate(timestamp + u64::from(minute) * 60)
+                        .into(),
+                })
+                .collect();
+
+            (
+                Some(auction.get_current_price_decay_rate(timestamp).into()),
+                projection,
+            )
+        };
+
+        Self {
+            location: model.location,
+            start_time: model.start_time,
+            start_price: model.start_price,
+            floor_price: model.floor_price,
+            decay_rate: auction.decay_rate,
+            is_finished: model.is_finished,
+            sold_at_price: model.sold_at_price,
+            current_price,
+            projection,
+        }
+    }
+}
+
+pub struct AuctionsRoute;
+
+impl Default for AuctionsRoute {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuctionsRoute {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn router(self) -> Router<AppState> {
+        Router::new()
+            .route("/", get(Self::get_active_auctions))
+            .route("/{location}", get(Self::get_auction))
+    }
+
+    async fn get_active_auctions(
+        State(auction_repository): State<Arc<AuctionRepository>>,
+        Query(query): Query<ProjectionQuery>,
+    ) -> Result<Json<Vec<AuctionPrice>>, StatusCode> {
+        let now = Utc::now();
+
+        let auctions = auction_repository
+            .get_active_auctions(now.naive_utc())
+            .await
+            .map_err(|err| {
+                error!("Error while fetching active auctions: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        Ok(Json(
+            auctions
+                .iter()
+                .map(|auction| AuctionPrice::at(auction, now, query.minutes.unwrap_or(0)))
+                .collect(),
+        ))
+    }
+
+    async fn get_auction(
+        State(auction_repository): State<Arc<AuctionRepository>>,
+        Path(location): Path<u64>,
+        Query(query): Query<ProjectionQuery>,
+    ) -> Result<Json<AuctionPrice>, StatusCode> {
+        let now = Utc::now();
+
+        let auction = auction_repository
+            .get_latest_at_location(Location::new(location), now.naive_utc())
+            .await
+            .map_err(|err| {
+                error!("Error while fetching auction at {}: {}", location, err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+        Ok(Json(AuctionPrice::at(
+            &auction,
+            now,
+            query.minutes.unwrap_or(0),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chaindata_models::events::EventId;
+
+    use super::*;
+
+    fn model(start_time: DateTime<Utc>, is_finished: bool) -> AuctionModel {
+        AuctionModel {
+            id: EventId::new_test(0, 0, 0),
+            at: start_time.naive_utc(),
+            location: Location::new(2080),
+            start_time: start_time.naive_utc(),
+            start_price: U256::from_str("1000000").unwrap(),
+            floor_price: U256::from_str("0").unwrap(),
+            is_finished,
+            decay_rate: 100,
+            sold_at_price: None,
+        }
+    }
+
+    #[test]
+    fn test_projection() {
+        let now = Utc::now();
+        let price = AuctionPrice::at(&model(now, false), now, 2);
+
+        assert_eq!(
+            price.current_price,
+            Some(U256::from_str("1000000").unwrap())
+        );
+        assert_eq!(price.projection.len(), 2);
+        // 1 minute IRL is 5 minutes of game time
+        assert_eq!(price.projection[0].price, U256::from_str("977500").unwrap());
+        assert!(price.projection[0].price > price.projection[1].price);
+    }
+
+    #[test]
+    fn test_projection_is_capped() {
+        let now = Utc::now();
+        let price = AuctionPrice::at(&model(now, false), now, u32::MAX);
+
+        assert_eq!(price.projection.len(), MAX_PROJECTION_MINUTES as usize);
+    }
+
+    #[test]
+    fn test_finished_auction_has_no_price() {
+        let now = Utc::now();
+        let price = AuctionPrice::at(&model(now, true), now, 10);
+
+        assert!(price.current_price.is_none());
+        assert!(price.projection.is_empty());
+    }
+}
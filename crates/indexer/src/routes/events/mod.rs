@@ -0,0 +1,246 @@
+mod stream;
+
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chaindata_models::{
+    events::{EventDataModel, EventId, EventType, FetchedEvent},
+    shared::{Location, U256},
+};
+use chaindata_repository::{EventFilter, EventRepository};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tracing::error;
+
+use crate::{
+    service::valuation::{Amount, ValuationService},
+    state::AppState,
+};
+
+/// Number of events returned when no limit is requested.
+const DEFAULT_EVENTS_LIMIT: u32 = 100;
+
+/// Maximum number of events returned in a single request.
+const MAX_EVENTS_LIMIT: u32 = 1000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventsQuery {
+    pub event_type: Option<EventType>,
+    /// Buyer, seller or owner involved in the event.
+    pub address: Option<String>,
+    pub location: Option<u64>,
+    /// Only return the events at or after this time.
+    pub from: Option<NaiveDateTime>,
+    /// Only return the events at or before this time.
+    pub to: Option<NaiveDateTime>,
+    /// Cursor returned by the previous page.
+    pub after: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl EventsQuery {
+    fn limit(&self) -> u32 {
+        self.limit
+            .unwrap_or(DEFAULT_EVENTS_LIMIT)
+            .clamp(1, MAX_EVENTS_LIMIT)
+    }
+
+    fn filter(&self) -> Result<EventFilter, StatusCode> {
+        Ok(EventFilter {
+            event_type: self.event_type.clone(),
+            address: self.address.as_deref().map(parse_address).transpose()?,
+            location: self.location.map(Location::new),
+            from: self.from,
+            to: self.to,
+        })
+    }
+}
+
+/// Parses an address, formatting it as in the events.
+fn parse_address(address: &str) -> Result<String, StatusCode> {
+    // Addresses of the events are stored with the shortest hex formatting
+    Felt::from_str(address)
+        .map(|address| format!("{address:#x}"))
+        .map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValuedEvent {
+    #[serde(flatten)]
+    pub event: FetchedEvent,
+    /// Price paid in the event in the main token, at the price of the token at that time.
+    ///
+    /// `None` if nothing was paid in the event, or if the price of the token was not known.
+    pub price_in_main_token: Option<U256>,
+}
+
+/// Builds the amount paid in an event, if any.
+fn paid_amount(event: &FetchedEvent, main_token: Felt) -> Option<Amount> {
+    let (token, price) = match &event.data {
+        EventDataModel::LandBought(data) => (Felt::from_str(&data.token_used).ok()?, data.price),
+        // Auctions are paid in the main token
+        EventDataModel::AuctionFinished(data) => (main_token, data.price),
+        _ => return None,
+    };
+
+    Some(Amount {
+        token,
+        amount: **price,
+        at: event.at,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventFeed {
+    pub events: Vec<ValuedEvent>,
+    /// Cursor to pass as `after` to get the next page, `None` if this is the last page.
+    pub next_cursor: Option<String>,
+}
+
+pub struct EventsRoute;
+
+impl Default for EventsRoute {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventsRoute {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn router(self) -> Router<AppState> {
+        Router::new()
+            .route("/", get(Self::get_events))
+            .route("/stream", get(Self::stream_events))
+            .route("/ws", get(Self::ws_events))
+    }
+
+    async fn get_events(
+        State(event_repository): State<Arc<EventRepository>>,
+        State(valuation_service): State<Arc<ValuationService>>,
+        Query(query): Query<EventsQuery>,
+    ) -> Result<Json<EventFeed>, StatusCode> {
+        let filter = query.filter()?;
+        let after = query
+            .after
+            .as_deref()
+            .map(EventId::from_str)
+            .transpose()
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+        let limit = query.limit();
+
+        let events = event_repository
+            .get_events(&filter, after, limit.into())
+            .await
+            .map_err(|err| {
+                error!("Error while fetching events: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        let next_cursor = if events.len() == limit as usize {
+            events.last().map(|event| event.id.as_string())
+        } else {
+            None
+        };
+
+        let main_token = valuation_service.main_token();
+        let amounts: Vec<Option<Amount>> = events
+            .iter()
+            .map(|event| paid_amount(event, main_token))
+            .collect();
+        let values = valuation_service
+            .to_main_token_sparse(&amounts)
+            .await
+            .map_err(|err| {
+                error!("Error while valuing events: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        let events = events
+            .into_iter()
+            .zip(values)
+            .map(|(event, price_in_main_token)| ValuedEvent {
+                event,
+                price_in_main_token,
+            })
+            .collect();
+
+        Ok(Json(EventFeed {
+            events,
+            next_cursor,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chaindata_models::events::actions::{AuctionFinishedEventModel, LandNukedEventModel};
+    use starknet::core::types::U256 as RawU256;
+
+    use super::*;
+
+    #[test]
+    fn test_filter_normalizes_address() {
+        let query = EventsQuery {
+            address: Some("0x000abc".to_string()),
+            location: Some(42),
+            ..EventsQuery::default()
+        };
+
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.address.as_deref(), Some("0xabc"));
+        assert_eq!(filter.location, Some(Location::new(42)));
+
+        let query = EventsQuery {
+            address: Some("not an address".to_string()),
+            ..EventsQuery::default()
+        };
+        assert_eq!(query.filter().unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_paid_amount() {
+        let main_token = Felt::from(1u8);
+        let event = |data| FetchedEvent {
+            id: EventId::new_test(0, 0, 0),
+            at: NaiveDateTime::default(),
+            data,
+        };
+
+        let auction = event(EventDataModel::AuctionFinished(AuctionFinishedEventModel {
+            id: None,
+            location: Location::new(1),
+            buyer: "0xa".to_string(),
+            price: U256::from_str("100").unwrap(),
+        }));
+        let amount = paid_amount(&auction, main_token).unwrap();
+        assert_eq!(amount.token, main_token);
+        assert_eq!(amount.amount, RawU256::from(100u8));
+
+        let nuke = event(EventDataModel::LandNuked(LandNukedEventModel {
+            id: None,
+            location: Location::new(1),
+            owner: "0xa".to_string(),
+        }));
+        assert!(paid_amount(&nuke, main_token).is_none());
+    }
+
+    #[test]
+    fn test_limit() {
+        assert_eq!(EventsQuery::default().limit(), DEFAULT_EVENTS_LIMIT);
+        let query = EventsQuery {
+            limit: Some(5000),
+            ..EventsQuery::default()
+        };
+        assert_eq!(query.limit(), MAX_EVENTS_LIMIT);
+    }
+}
@@ -0,0 +1,228 @@
+use std::{pin::pin, sync::Arc};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::{
+        sse::{Event as SseEvent, KeepAlive},
+        Response, Sse,
+    },
+};
+use chaindata_models::{
+    events::{EventDataModel, FetchedEvent},
+    shared::Location,
+};
+use chaindata_service::ChainDataService;
+use futures_util::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use tokio::{select, sync::broadcast};
+use tracing::{debug, error, warn};
+
+use super::{parse_address, EventsRoute};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamQuery {
+    pub location: Option<u64>,
+    /// Buyer, seller or owner involved in the event.
+    pub address: Option<String>,
+}
+
+impl StreamQuery {
+    fn filter(&self) -> Result<StreamFilter, StatusCode> {
+        Ok(StreamFilter {
+            location: self.location.map(Location::new),
+            address: self.address.as_deref().map(parse_address).transpose()?,
+        })
+    }
+}
+
+/// Selects the live events sent to a subscriber.
+#[derive(Debug, Clone)]
+struct StreamFilter {
+    location: Option<Location>,
+    address: Option<String>,
+}
+
+impl StreamFilter {
+    fn matches(&self, event: &FetchedEvent) -> bool {
+        // Only the events of the game are streamed
+        let is_game_event = matches!(
+            event.data,
+            EventDataModel::LandBought(_)
+                | EventDataModel::LandNuked(_)
+                | EventDataModel::NewAuction(_)
+                | EventDataModel::AuctionFinished(_)
+        );
+
+        is_game_event
+            && self
+                .location
+                .is_none_or(|location| event.data.location() == Some(location))
+            && self
+                .address
+                .as_deref()
+                .is_none_or(|address| event.data.involves(address))
+    }
+}
+
+/// Turns a subscription to the new events into a stream of the events matching the filter.
+fn live_events(
+    receiver: broadcast::Receiver<FetchedEvent>,
+    filter: StreamFilter,
+) -> impl Stream<Item = FetchedEvent> {
+    stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if filter.matches(&event) => return Some((event, (receiver, filter))),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Live events subscriber is lagging, skipped {} events",
+                        skipped
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+impl EventsRoute {
+    /// Streams the new events as server-sent events.
+    pub(super) async fn stream_events(
+        State(chaindata_service): State<Arc<ChainDataService>>,
+        Query(query): Query<StreamQuery>,
+    ) -> Result<Sse<impl Stream<Item = Result<SseEvent, axum::Error>>>, StatusCode> {
+        let filter = query.filter()?;
+        let events = live_events(chaindata_service.subscribe_events(), filter)
+            .map(|event| SseEvent::default().json_data(event));
+
+        Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+    }
+
+    /// Streams the new events over a WebSocket, as JSON text messages.
+    pub(super) async fn ws_events(
+        ws: WebSocketUpgrade,
+        State(chaindata_service): State<Arc<ChainDataService>>,
+        Query(query): Query<StreamQuery>,
+    ) -> Result<Response, StatusCode> {
+        let filter = query.filter()?;
+        // Subscribe before the upgrade, so that no event is missed in between
+        let events = live_events(chaindata_service.subscribe_events(), filter);
+
+        Ok(ws.on_upgrade(move |socket| forward_events(socket, events)))
+    }
+}
+
+async fn forward_events(mut socket: WebSocket, events: impl Stream<Item = FetchedEvent>) {
+    let mut events = pin!(events);
+
+    loop {
+        select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                let message = match serde_json::to_string(&event) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        error!("Error while serializing a live event: {}", err);
+                        continue;
+                    }
+                };
+
+                if socket.send(Message::Text(message.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                // Clients are not expected to send anything, but to close the connection
+                match message {
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+
+    debug!("Live events WebSocket closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chaindata_models::{
+        events::{
+            actions::{LandBoughtEventModel, NewAuctionEventModel},
+            auth::AddressAuthorizedEventModel,
+            EventId,
+        },
+        shared::U256,
+    };
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    fn event(data: EventDataModel) -> FetchedEvent {
+        FetchedEvent {
+            id: EventId::new_test(0, 0, 0),
+            at: NaiveDateTime::default(),
+            data,
+        }
+    }
+
+    fn land_bought(location: u64, buyer: &str, seller: &str) -> FetchedEvent {
+        event(EventDataModel::LandBought(LandBoughtEventModel {
+            id: None,
+            location: Location::new(location),
+            buyer: buyer.to_string(),
+            seller: seller.to_string(),
+            price: U256::from_str("100").unwrap(),
+            token_used: "0x1".to_string(),
+        }))
+    }
+
+    #[test]
+    fn test_stream_filter() {
+        let all = StreamQuery::default().filter().unwrap();
+        assert!(all.matches(&land_bought(1, "0xa", "0xb")));
+        assert!(
+            all.matches(&event(EventDataModel::NewAuction(NewAuctionEventModel {
+                id: None,
+                location: Location::new(1),
+                starting_price: U256::from_str("100").unwrap(),
+                floor_price: U256::from_str("1").unwrap(),
+            })))
+        );
+        assert!(!all.matches(&event(EventDataModel::AddressAuthorized(
+            AddressAuthorizedEventModel {
+                id: None,
+                at: NaiveDateTime::default(),
+                address: "0xa".to_string(),
+            }
+        ))));
+
+        let by_location = StreamQuery {
+            location: Some(1),
+            ..StreamQuery::default()
+        }
+        .filter()
+        .unwrap();
+        assert!(by_location.matches(&land_bought(1, "0xa", "0xb")));
+        assert!(!by_location.matches(&land_bought(2, "0xa", "0xb")));
+
+        let by_address = StreamQuery {
+            address: Some("0x00b".to_string()),
+            ..StreamQuery::default()
+        }
+        .filter()
+        .unwrap();
+        assert!(by_address.matches(&land_bought(1, "0xa", "0xb")));
+        assert!(!by_address.matches(&land_bought(1, "0xa", "0xc")));
+    }
+}
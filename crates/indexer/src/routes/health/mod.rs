@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use chaindata_service::{tasks::status::TaskStatus, ChainDataService};
+
+use crate::state::AppState;
+
+pub struct HealthRoute;
+
+impl Default for HealthRoute {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthRoute {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn router(self) -> Router<AppState> {
+        Router::new()
+            .route("/health", get(Self::health))
+            .route("/ready", get(Self::ready))
+    }
+
+    /// Liveness: fails if one of the chaindata tasks crashed and is waiting to be restarted.
+    #[allow(clippy::unused_async)] // required for axum
+    async fn health(
+        State(chaindata_service): State<Arc<ChainDataService>>,
+    ) -> (StatusCode, Json<Vec<TaskStatus>>) {
+        Self::respond(chaindata_service.is_alive(), chaindata_service.status())
+    }
+
+    /// Readiness: fails if the ingestion did not succeed recently, as the data would be stale.
+    #[allow(clippy::unused_async)] // required for axum
+    async fn ready(
+        State(chaindata_service): State<Arc<ChainDataService>>,
+    ) -> (StatusCode, Json<Vec<TaskStatus>>) {
+        Self::respond(chaindata_service.is_ready(), chaindata_service.status())
+    }
+
+    fn respond(ok: bool, status: Vec<TaskStatus>) -> (StatusCode, Json<Vec<TaskStatus>>) {
+        let code = if ok {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        (code, Json(status))
+    }
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chaindata_models::{
+    models::LandModel,
+    shared::{Location, U256},
+};
+use chaindata_repository::{LandRepository, LandStakeRepository};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use ponziland_models::{
+    helpers::taxes::{get_tax_rate_per_neighbor, get_taxes_per_neighbor, get_time_to_nuke},
+    models::{Land, LandStake},
+};
+use serde::Serialize;
+use starknet::core::types::{Felt, U256 as RawU256};
+use torii_ingester::u256::U256 as ToriiU256;
+use tracing::error;
+
+use super::LandsRoute;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LandHealth {
+    pub location: Location,
+    /// Neighbors that have an owner, and are therefore receiving taxes from this land.
+    pub neighbors: Vec<Location>,
+    pub max_neighbors: u8,
+    /// Taxes paid to each neighbor every hour.
+    pub tax_rate_per_neighbor: U256,
+    /// Taxes paid to all the neighbors every hour.
+    pub burn_rate: U256,
+    /// Taxes accumulated for each neighbor since the last payment.
+    pub taxes_per_neighbor: U256,
+    pub stake_amount: U256,
+    /// Stake that is left once all the accumulated taxes are paid.
+    pub remaining_stake: U256,
+    pub last_pay_time: NaiveDateTime,
+    /// Number of seconds before the land can be nuked, 0 if it already can or if it pays no taxes.
+    pub time_to_nuke: U256,
+    /// Estimated time of the nuke, `None` if the land does not pay any taxes.
+    pub nuke_at: Option<NaiveDateTime>,
+}
+
+fn to_model_u256(value: RawU256) -> U256 {
+    ToriiU256::from(value).into()
+}
+
+impl LandHealth {
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    fn compute(
+        land: &Land,
+        land_stake: &LandStake,
+        neighbors: Vec<Location>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let current_time = now.timestamp() as u64;
+        // There are at most 8 neighbors
+        let num_neighbors = neighbors.len() as u8;
+
+        let tax_rate_per_neighbor = get_tax_rate_per_neighbor(land);
+        let taxes_per_neighbor = get_taxes_per_neighbor(land, land_stake, current_time);
+        let time_to_nuke = get_time_to_nuke(land, land_stake, num_neighbors, current_time);
+
+        let burn_rate = *tax_rate_per_neighbor * RawU256::from(num_neighbors);
+        let taxes_due = *taxes_per_neighbor * RawU256::from(num_neighbors);
+        let remaining_stake = if *land_stake.amount > taxes_due {
+            *land_stake.amount - taxes_due
+        } else {
+            RawU256::from(0u8)
+        };
+
+        let nuke_at = (burn_rate != RawU256::from(0u8)).then(|| {
+            // A nuke further than i64::MAX seconds in the future is never going to happen
+            let seconds = if time_to_nuke.high() == 0 {
+                i64::try_from(time_to_nuke.low()).unwrap_or(i64::MAX)
+            } else {
+                i64::MAX
+            };
+
+            now.checked_add_signed(Duration::seconds(seconds))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC)
+                .naive_utc()
+        });
+
+        Self {
+            location: land.location.into(),
+            neighbors,
+            max_neighbors: land.location.max_neighbors(),
+            tax_rate_per_neighbor: tax_rate_per_neighbor.into(),
+            burn_rate: to_model_u256(burn_rate),
+            taxes_per_neighbor: taxes_per_neighbor.into(),
+            stake_amount: land_stake.amount.into(),
+            remaining_stake: to_model_u256(remaining_stake),
+            last_pay_time: DateTime::from_timestamp(land_stake.last_pay_time as i64, 0)
+                .unwrap_or_default()
+                .naive_utc(),
+            time_to_nuke: time_to_nuke.into(),
+            nuke_at,
+        }
+    }
+}
+
+fn internal_error(err: impl std::fmt::Display) -> StatusCode {
+    error!("Error while computing land health: {}", err);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Converts a land model, returning `None` for lands without any owner.
+fn owned_land(model: &LandModel) -> Result<Option<Land>, StatusCode> {
+    let land = Land::try_from(model).map_err(internal_error)?;
+    Ok(Some(land).filter(|land| land.owner != Felt::ZERO))
+}
+
+impl LandsRoute {
+    pub(super) async fn get_health(
+        State(land_repository): State<Arc<LandRepository>>,
+        State(land_stake_repository): State<Arc<LandStakeRepository>>,
+        Path(location): Path<u64>,
+    ) -> Result<Json<LandHealth>, StatusCode> {
+        let now = Utc::now();
+        let location = Location::new(location);
+
+        let land = land_repository
+            .get_latest_at_location(location, now.naive_utc())
+            .await
+            .map_err(internal_error)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let land = owned_land(&land)?.ok_or(StatusCode::NOT_FOUND)?;
+
+        let land_stake = land_stake_repository
+            .get_latest_at_location(location, now.naive_utc())
+            .await
+            .map_err(internal_error)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+        // Only the neighbors with an owner are receiving taxes
+        let mut neighbors = Vec::new();
+        for neighbor in location.get_all_neighbors() {
+            let neighbor = neighbor.into();
+            if let Some(model) = land_repository
+                .get_latest_at_location(neighbor, now.naive_utc())
+                .await
+                .map_err(internal_error)?
+            {
+                if owned_land(&model)?.is_some() {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+
+        Ok(Json(LandHealth::compute(
+            &land,
+            &LandStake::from(&land_stake),
+            neighbors,
+            now,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ponziland_models::models::Level;
+
+    use super::*;
+
+    fn land(sell_price: u64) -> Land {
+        Land {
+            location: ponziland_models::shared::Location(2080),
+            block_date_bought: 0,
+            owner: Felt::ONE,
+            sell_price: ToriiU256::from(sell_price),
+            token_used: Felt::TWO,
+            level: Level::Zero,
+        }
+    }
+
+    fn stake(last_pay_time: u64, amount: u64) -> LandStake {
+        LandStake {
+            location: ponziland_models::shared::Location(2080),
+            last_pay_time,
+            amount: ToriiU256::from(amount),
+        }
+    }
+
+    #[test]
+    fn test_health_with_neighbors() {
+        let now = DateTime::from_timestamp(1000 + 1800, 0).unwrap();
+        let neighbors = vec![Location::new(2079), Location::new(2081)];
+
+        let health = LandHealth::compute(&land(1_000_000), &stake(1000, 100_000), neighbors, now);
+
+        // 12_500 per neighbor per hour
+        assert_eq!(health.burn_rate, to_model_u256(RawU256::from(25_000u32)));
+        assert_eq!(
+            health.taxes_per_neighbor,
+            to_model_u256(RawU256::from(6_250u32))
+        );
+        assert_eq!(
+            health.remaining_stake,
+            to_model_u256(RawU256::from(87_500u32))
+        );
+        // The stake lasts 4 hours after the last payment
+        assert_eq!(
+            health.time_to_nuke,
+            to_model_u256(RawU256::from(4u32 * 3600 - 1800))
+        );
+        assert_eq!(
+            health.nuke_at,
+            DateTime::from_timestamp(1000 + 4 * 3600, 0).map(|date| date.naive_utc())
+        );
+    }
+
+    #[test]
+    fn test_health_without_neighbors() {
+        let now = DateTime::from_timestamp(2000, 0).unwrap();
+
+        let health = LandHealth::compute(&land(1_000_000), &stake(1000, 100_000), vec![], now);
+
+        assert_eq!(health.max_neighbors, 8);
+        assert_eq!(health.remaining_stake, health.stake_amount);
+        assert!(health.nuke_at.is_none());
+    }
+}
@@ -0,0 +1,157 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chaindata_models::{
+    models::LandHistoryModel,
+    shared::{Location, U256},
+};
+use chaindata_repository::LandRepository;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tracing::error;
+
+use crate::service::valuation::{Amount, ValuationService};
+
+use super::LandsRoute;
+
+/// Number of history entries returned when no limit is requested.
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+
+/// Maximum number of history entries returned in a single request.
+const MAX_HISTORY_LIMIT: u32 = 1000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Only return the changes at or after this time.
+    pub from: Option<NaiveDateTime>,
+    /// Only return the changes at or before this time.
+    pub to: Option<NaiveDateTime>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+impl HistoryQuery {
+    fn limit(&self) -> u32 {
+        self.limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .min(MAX_HISTORY_LIMIT)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValuedLandHistory {
+    #[serde(flatten)]
+    pub entry: LandHistoryModel,
+    /// Sell price in the main token, at the price of the token at that time.
+    pub sell_price_in_main_token: Option<U256>,
+    /// Stake in the main token, at the price of the token at that time.
+    pub stake_amount_in_main_token: Option<U256>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LandHistory {
+    pub location: Location,
+    pub limit: u32,
+    pub offset: u32,
+    /// Each time the land or its stake changed, from the oldest to the most recent.
+    pub history: Vec<ValuedLandHistory>,
+}
+
+/// Builds the amount of the token used by the land at the time of the entry.
+fn amount(entry: &LandHistoryModel, amount: Option<U256>) -> Option<Amount> {
+    Some(Amount {
+        token: Felt::from_str(entry.token_used.as_deref()?).ok()?,
+        amount: **amount?,
+        at: entry.at,
+    })
+}
+
+impl LandsRoute {
+    pub(super) async fn get_history(
+        State(land_repository): State<Arc<LandRepository>>,
+        State(valuation_service): State<Arc<ValuationService>>,
+        Path(location): Path<u64>,
+        Query(query): Query<HistoryQuery>,
+    ) -> Result<Json<LandHistory>, StatusCode> {
+        let location = Location::new(location);
+        let limit = query.limit();
+        let offset = query.offset.unwrap_or(0);
+
+        let history = land_repository
+            .get_history(location, query.from, query.to, limit.into(), offset.into())
+            .await
+            .map_err(|err| {
+                error!("Error while fetching land history: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        // Sell prices then stakes
+        let amounts: Vec<Option<Amount>> = history
+            .iter()
+            .map(|entry| amount(entry, entry.sell_price))
+            .chain(
+                history
+                    .iter()
+                    .map(|entry| amount(entry, entry.stake_amount)),
+            )
+            .collect();
+        let mut values = valuation_service
+            .to_main_token_sparse(&amounts)
+            .await
+            .map_err(|err| {
+                error!("Error while valuing land history: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        let stake_values = values.split_off(history.len());
+
+        let history = history
+            .into_iter()
+            .zip(values.into_iter().zip(stake_values))
+            .map(
+                |(entry, (sell_price_value, stake_value))| ValuedLandHistory {
+                    entry,
+                    sell_price_in_main_token: sell_price_value,
+                    stake_amount_in_main_token: stake_value,
+                },
+            )
+            .collect();
+
+        Ok(Json(LandHistory {
+            location,
+            limit,
+            offset,
+            history,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_limit() {
+        assert_eq!(HistoryQuery::default().limit(), DEFAULT_HISTORY_LIMIT);
+        assert_eq!(
+            HistoryQuery {
+                limit: Some(10),
+                ..Default::default()
+            }
+            .limit(),
+            10
+        );
+        assert_eq!(
+            HistoryQuery {
+                limit: Some(1_000_000),
+                ..Default::default()
+            }
+            .limit(),
+            MAX_HISTORY_LIMIT
+        );
+    }
+}
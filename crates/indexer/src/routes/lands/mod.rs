@@ -1,3 +1,7 @@
+mod health;
+mod history;
+mod snapshot;
+
 use axum::{extract::State, routing::get, Json, Router};
 use chaindata_repository::LandRepository;
 use serde::Serialize;
@@ -53,7 +57,11 @@ impl LandsRoute {
     }
 
     pub fn router(self) -> Router<AppState> {
-        Router::new().route("/distribution", get(Self::get_distribution))
+        Router::new()
+            .route("/", get(Self::get_snapshot))
+            .route("/distribution", get(Self::get_distribution))
+            .route("/{location}/health", get(Self::get_health))
+            .route("/{location}/history", get(Self::get_history))
     }
 
     #[allow(clippy::cast_precision_loss)]
@@ -0,0 +1,151 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chaindata_models::{
+    models::{LandModel, LandStakeModel},
+    shared::{Location, U256},
+};
+use chaindata_repository::{LandRepository, LandStakeRepository};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::LandsRoute;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SnapshotQuery {
+    /// Instant of the snapshot, now if not given.
+    pub at: Option<NaiveDateTime>,
+}
+
+/// State of the whole board at a given instant, encoded by column to keep the payload small.
+///
+/// All the columns have the same length, and the i-th element of each column describes the
+/// land at `locations[i]`. Tokens are dictionary encoded: `token_indices[i]` is an index in
+/// `tokens`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LandSnapshot {
+    pub at: NaiveDateTime,
+    pub locations: Vec<Location>,
+    pub owners: Vec<String>,
+    pub sell_prices: Vec<U256>,
+    pub levels: Vec<u8>,
+    pub tokens: Vec<String>,
+    pub token_indices: Vec<usize>,
+    /// Stake of each land, `None` if the land was never staked.
+    pub stake_amounts: Vec<Option<U256>>,
+}
+
+impl LandSnapshot {
+    fn build(at: NaiveDateTime, mut lands: Vec<LandModel>, stakes: Vec<LandStakeModel>) -> Self {
+        let stakes: HashMap<u64, U256> = stakes
+            .into_iter()
+            .map(|stake| ((*stake.location).0, stake.amount))
+            .collect();
+
+        lands.sort_by_key(|land| (*land.location).0);
+
+        let mut snapshot = Self {
+            at,
+            ..Default::default()
+        };
+        let mut token_indices = HashMap::new();
+
+        for land in lands {
+            let token_index = *token_indices
+                .entry(land.token_used.clone())
+                .or_insert_with(|| {
+                    snapshot.tokens.push(land.token_used.clone());
+                    snapshot.tokens.len() - 1
+                });
+
+            snapshot
+                .stake_amounts
+                .push(stakes.get(&(*land.location).0).copied());
+            snapshot.locations.push(land.location);
+            snapshot.owners.push(land.owner);
+            snapshot.sell_prices.push(land.sell_price);
+            snapshot.levels.push(land.level as u8);
+            snapshot.token_indices.push(token_index);
+        }
+
+        snapshot
+    }
+}
+
+impl LandsRoute {
+    pub(super) async fn get_snapshot(
+        State(land_repository): State<Arc<LandRepository>>,
+        State(land_stake_repository): State<Arc<LandStakeRepository>>,
+        Query(query): Query<SnapshotQuery>,
+    ) -> Result<Json<LandSnapshot>, StatusCode> {
+        let at = query.at.unwrap_or_else(|| Utc::now().naive_utc());
+
+        let (lands, stakes) = tokio::try_join!(
+            land_repository.get_all_at_time(at),
+            land_stake_repository.get_all_at_time(at)
+        )
+        .map_err(|err| {
+            error!("Error while fetching the board snapshot: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        Ok(Json(LandSnapshot::build(at, lands, stakes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chaindata_models::{events::EventId, models::Level};
+
+    use super::*;
+
+    fn land(location: u64, token: &str) -> LandModel {
+        LandModel {
+            id: EventId::new_test(location, 0, 0),
+            at: NaiveDateTime::default(),
+            location: Location::new(location),
+            bought_at: NaiveDateTime::default(),
+            owner: format!("0xowner{location}"),
+            sell_price: U256::from_str("100").unwrap(),
+            token_used: token.to_string(),
+            level: Level::First,
+        }
+    }
+
+    #[test]
+    fn test_build_snapshot() {
+        let stake = LandStakeModel {
+            id: EventId::new_test(1, 0, 0),
+            at: NaiveDateTime::default(),
+            location: Location::new(2),
+            last_pay_time: NaiveDateTime::default(),
+            amount: U256::from_str("50").unwrap(),
+        };
+
+        let snapshot = LandSnapshot::build(
+            NaiveDateTime::default(),
+            vec![land(3, "0xa"), land(1, "0xb"), land(2, "0xa")],
+            vec![stake],
+        );
+
+        assert_eq!(
+            snapshot.locations,
+            vec![Location::new(1), Location::new(2), Location::new(3)]
+        );
+        assert_eq!(snapshot.owners, vec!["0xowner1", "0xowner2", "0xowner3"]);
+        assert_eq!(snapshot.tokens, vec!["0xb", "0xa"]);
+        assert_eq!(snapshot.token_indices, vec![0, 1, 1]);
+        assert_eq!(snapshot.levels, vec![1, 1, 1]);
+        assert_eq!(
+            snapshot.stake_amounts,
+            vec![None, Some(U256::from_str("50").unwrap()), None]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chaindata_models::models::{LeaderboardEntryModel, LeaderboardKind};
+use chaindata_repository::LeaderboardRepository;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::state::AppState;
+
+/// Maximum number of snapshots returned in a single request.
+const MAX_SNAPSHOTS: u32 = 100;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LeaderboardQuery {
+    /// Return the snapshots computed at or after this time, instead of only the last one.
+    pub from: Option<NaiveDateTime>,
+    /// Return the snapshots computed at or before this time, instead of only the last one.
+    pub to: Option<NaiveDateTime>,
+    /// Maximum number of snapshots, the most recent ones are kept.
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    pub rank: i32,
+    pub address: String,
+    /// Decimal score, amounts are in the main token.
+    pub score: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardSnapshot {
+    pub computed_at: NaiveDateTime,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Leaderboard {
+    pub kind: LeaderboardKind,
+    /// Snapshots from the oldest to the most recent.
+    pub snapshots: Vec<LeaderboardSnapshot>,
+}
+
+impl Leaderboard {
+    /// Groups the entries, ordered by time then rank, into snapshots.
+    fn from_entries(kind: LeaderboardKind, entries: Vec<LeaderboardEntryModel>) -> Self {
+        let mut snapshots: Vec<LeaderboardSnapshot> = Vec::new();
+
+        for entry in entries {
+            let entry_at = entry.computed_at;
+            let entry = LeaderboardEntry {
+                rank: entry.rank,
+                address: entry.address,
+                score: entry.score.to_string(),
+            };
+
+            match snapshots.last_mut() {
+                Some(snapshot) if snapshot.computed_at == entry_at => snapshot.entries.push(entry),
+                _ => snapshots.push(LeaderboardSnapshot {
+                    computed_at: entry_at,
+                    entries: vec![entry],
+                }),
+            }
+        }
+
+        Self { kind, snapshots }
+    }
+}
+
+pub struct LeaderboardsRoute;
+
+impl Default for LeaderboardsRoute {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LeaderboardsRoute {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn router(self) -> Router<AppState> {
+        Router::new().route("/{kind}", get(Self::get_leaderboard))
+    }
+
+    async fn get_leaderboard(
+        State(leaderboard_repository): State<Arc<LeaderboardRepository>>,
+        Path(kind): Path<LeaderboardKind>,
+        Query(query): Query<LeaderboardQuery>,
+    ) -> Result<Json<Leaderboard>, StatusCode> {
+        let entries = if query.from.is_none() && query.to.is_none() {
+            leaderboard_repository.get_latest(kind).await
+        } else {
+            let limit = query.limit.unwrap_or(MAX_SNAPSHOTS).min(MAX_SNAPSHOTS);
+            leaderboard_repository
+                .get_history(kind, query.from, query.to, limit.into())
+                .await
+        }
+        .map_err(|err| {
+            error!("Error while fetching the leaderboard: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        Ok(Json(Leaderboard::from_entries(kind, entries)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use sqlx::types::BigDecimal;
+
+    use super::*;
+
+    #[test]
+    fn test_from_entries() {
+        let entry = |at: i64, rank: i32| LeaderboardEntryModel {
+            kind: LeaderboardKind::AuctionsWon,
+            computed_at: DateTime::from_timestamp(at, 0).unwrap().naive_utc(),
+            rank,
+            address: format!("0x{rank}"),
+            score: BigDecimal::from(10 - rank),
+        };
+
+        let leaderboard = Leaderboard::from_entries(
+            LeaderboardKind::AuctionsWon,
+            vec![entry(1, 1), entry(1, 2), entry(2, 1)],
+        );
+
+        assert_eq!(leaderboard.snapshots.len(), 2);
+        assert_eq!(leaderboard.snapshots[0].entries.len(), 2);
+        assert_eq!(leaderboard.snapshots[0].entries[1].score, "8");
+        assert_eq!(leaderboard.snapshots[1].entries[0].address, "0x1");
+    }
+}
@@ -1,3 +1,9 @@
+pub mod admin;
+pub mod auctions;
+pub mod events;
+pub mod health;
 pub mod lands;
+pub mod leaderboards;
+pub mod players;
 pub mod price;
 pub mod tokens;
@@ -0,0 +1,291 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chaindata_models::{
+    models::{LandModel, LandStakeModel, Level},
+    shared::{Location, U256},
+};
+use chaindata_repository::{LandRepository, LandStakeRepository};
+use chrono::Utc;
+use serde::Serialize;
+use starknet::core::types::{Felt, U256 as RawU256};
+use thiserror::Error;
+use torii_ingester::u256::U256 as ToriiU256;
+use tracing::error;
+
+use crate::{
+    service::{ekubo::EkuboService, token::TokenService},
+    state::AppState,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioLand {
+    pub location: Location,
+    pub token_used: String,
+    pub sell_price: U256,
+    pub level: Level,
+    pub stake_amount: Option<U256>,
+    /// Sell price and stake of the land in the main token, `None` if the token has no price.
+    pub value: Option<U256>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Portfolio {
+    pub address: String,
+    /// Address of the token in which the values are expressed.
+    pub main_token: String,
+    pub lands: Vec<PortfolioLand>,
+    /// Sum of the sell prices of the lands, in the main token.
+    pub total_sell_price: U256,
+    /// Sum of the stakes of the lands, in the main token.
+    pub total_stake: U256,
+    pub total_value: U256,
+    /// Tokens without a known price, whose lands are not counted in the totals.
+    pub unpriced_tokens: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+#[error("The value of the portfolio does not fit in a u256")]
+pub struct PortfolioOverflow;
+
+impl Portfolio {
+    /// Builds the portfolio of an address, converting each amount with `to_main_token`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value or a total does not fit in a u256.
+    fn compute(
+        address: String,
+        main_token: String,
+        lands: Vec<(LandModel, Option<LandStakeModel>)>,
+        to_main_token: impl Fn(Felt, RawU256) -> Option<RawU256>,
+    ) -> Result<Self, PortfolioOverflow> {
+        let mut total_sell_price = ToriiU256::from(0u8);
+        let mut total_stake = ToriiU256::from(0u8);
+        let mut unpriced_tokens = Vec::new();
+
+        let lands = lands
+            .into_iter()
+            .map(|(land, stake)| {
+                let stake_amount = stake.map(|stake| stake.amount);
+
+                let converted = Felt::from_str(&land.token_used).ok().and_then(|token| {
+                    let sell_price = to_main_token(token, **land.sell_price)?;
+                    let stake = stake_amount.map_or(RawU256::from(0u8), |amount| **amount);
+                    let stake = to_main_token(token, stake)?;
+                    Some((sell_price, stake))
+                });
+
+                let value = if let Some((sell_price, stake)) = converted {
+                    let (sell_price, stake) = (ToriiU256::from(sell_price), ToriiU256::from(stake));
+                    total_sell_price = total_sell_price
+                        .checked_add(sell_price)
+                        .ok_or(PortfolioOverflow)?;
+                    total_stake = total_stake.checked_add(stake).ok_or(PortfolioOverflow)?;
+                    Some(
+                        sell_price
+                            .checked_add(stake)
+                            .ok_or(PortfolioOverflow)?
+                            .into(),
+                    )
+                } else {
+                    if !unpriced_tokens.contains(&land.token_used) {
+                        unpriced_tokens.push(land.token_used.clone());
+                    }
+                    None
+                };
+
+                Ok(PortfolioLand {
+                    location: land.location,
+                    token_used: land.token_used,
+                    sell_price: land.sell_price,
+                    level: land.level,
+                    stake_amount,
+                    value,
+                })
+            })
+            .collect::<Result<Vec<_>, PortfolioOverflow>>()?;
+
+        Ok(Self {
+            address,
+            main_token,
+            lands,
+            total_sell_price: total_sell_price.into(),
+            total_stake: total_stake.into(),
+            total_value: total_sell_price
+                .checked_add(total_stake)
+                .ok_or(PortfolioOverflow)?
+                .into(),
+            unpriced_tokens,
+        })
+    }
+}
+
+fn internal_error(err: impl std::fmt::Display) -> StatusCode {
+    error!("Error while computing player portfolio: {}", err);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+pub struct PlayersRoute;
+
+impl Default for PlayersRoute {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayersRoute {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn router(self) -> Router<AppState> {
+        Router::new().route("/{address}", get(Self::get_portfolio))
+    }
+
+    async fn get_portfolio(
+        State(land_repository): State<Arc<LandRepository>>,
+        State(land_stake_repository): State<Arc<LandStakeRepository>>,
+        State(ekubo_service): State<Arc<EkuboService>>,
+        State(token_service): State<Arc<TokenService>>,
+        Path(address): Path<String>,
+    ) -> Result<Json<Portfolio>, StatusCode> {
+        let now = Utc::now().naive_utc();
+        // Owners are stored with the default felt formatting
+        let address = Felt::from_str(&address).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+        let owned = land_repository
+            .get_owned_by(&address.to_string(), now)
+            .await
+            .map_err(internal_error)?;
+
+        // Stakes of every land are read at once rather than one query per owned land
+        let mut stakes: HashMap<u64, LandStakeModel> = land_stake_repository
+            .get_all_at_time(now)
+            .await
+            .map_err(internal_error)?
+            .into_iter()
+            .map(|stake| ((*stake.location).0, stake))
+            .collect();
+        let lands = owned
+            .into_iter()
+            .map(|land| {
+                let stake = stakes.remove(&(*land.location).0);
+                (land, stake)
+            })
+            .collect();
+
+        Portfolio::compute(
+            address.to_fixed_hex_string(),
+            token_service.main_token().address.to_fixed_hex_string(),
+            lands,
+            |token, amount| ekubo_service.convert_to_main_token(token, amount),
+        )
+        .map(Json)
+        .map_err(internal_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chaindata_models::events::EventId;
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    fn to_shared(amount: RawU256) -> U256 {
+        ToriiU256::from(amount).into()
+    }
+
+    fn land(location: u64, token: Felt, sell_price: u64) -> LandModel {
+        LandModel {
+            id: EventId::new_test(location, 0, 0),
+            at: NaiveDateTime::default(),
+            location: Location::new(location),
+            bought_at: NaiveDateTime::default(),
+            owner: "1".to_string(),
+            sell_price: to_shared(RawU256::from(sell_price)),
+            token_used: token.to_string(),
+            level: Level::Zero,
+        }
+    }
+
+    fn stake(location: u64, amount: u64) -> LandStakeModel {
+        LandStakeModel {
+            id: EventId::new_test(location, 1, 0),
+            at: NaiveDateTime::default(),
+            location: Location::new(location),
+            last_pay_time: NaiveDateTime::default(),
+            amount: to_shared(RawU256::from(amount)),
+        }
+    }
+
+    #[test]
+    fn test_compute_portfolio() {
+        let main = Felt::ONE;
+        let other = Felt::TWO;
+        let unpriced = Felt::THREE;
+
+        // The other token is worth half of the main one
+        let to_main_token = |token: Felt, amount: RawU256| {
+            if token == main {
+                Some(amount)
+            } else if token == other {
+                Some(amount / RawU256::from(2u8))
+            } else {
+                None
+            }
+        };
+
+        let portfolio = Portfolio::compute(
+            "0x1".to_string(),
+            "0x2".to_string(),
+            vec![
+                (land(1, main, 100), Some(stake(1, 10))),
+                (land(2, other, 100), None),
+                (land(3, unpriced, 100), Some(stake(3, 10))),
+            ],
+            to_main_token,
+        )
+        .expect("the portfolio fits in a u256");
+
+        assert_eq!(portfolio.lands.len(), 3);
+        assert_eq!(
+            portfolio.lands[0].value,
+            Some(to_shared(RawU256::from(110u8)))
+        );
+        assert_eq!(
+            portfolio.lands[1].value,
+            Some(to_shared(RawU256::from(50u8)))
+        );
+        assert_eq!(portfolio.lands[2].value, None);
+
+        assert_eq!(portfolio.total_sell_price, to_shared(RawU256::from(150u8)));
+        assert_eq!(portfolio.total_stake, to_shared(RawU256::from(10u8)));
+        assert_eq!(portfolio.total_value, to_shared(RawU256::from(160u8)));
+        assert_eq!(portfolio.unpriced_tokens, vec![unpriced.to_string()]);
+    }
+
+    #[test]
+    fn test_compute_portfolio_overflow() {
+        let max = RawU256::from_words(u128::MAX, u128::MAX);
+
+        let result = Portfolio::compute(
+            "0x1".to_string(),
+            "0x2".to_string(),
+            vec![
+                (land(1, Felt::ONE, 100), Some(stake(1, 10))),
+                (land(2, Felt::ONE, 100), None),
+            ],
+            |_, amount| Some(max - amount),
+        );
+
+        assert!(result.is_err());
+    }
+}
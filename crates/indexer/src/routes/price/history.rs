@@ -0,0 +1,164 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chaindata_models::{models::PriceCandleModel, shared::U256};
+use chaindata_repository::TokenPriceRepository;
+use chrono::{Duration, NaiveDateTime, Utc};
+use ekubo::{math::u256fd128::U256FD128, price::PairRatio};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tracing::error;
+
+use super::{Price, PriceRoute};
+
+/// Number of candles returned when no start time is requested.
+const DEFAULT_CANDLES: i32 = 100;
+
+/// Maximum number of candles that can be requested at once.
+const MAX_CANDLES: i64 = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[default]
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "4h")]
+    FourHours,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl PriceInterval {
+    fn duration(self) -> Duration {
+        match self {
+            PriceInterval::OneMinute => Duration::minutes(1),
+            PriceInterval::FiveMinutes => Duration::minutes(5),
+            PriceInterval::FifteenMinutes => Duration::minutes(15),
+            PriceInterval::OneHour => Duration::hours(1),
+            PriceInterval::FourHours => Duration::hours(4),
+            PriceInterval::OneDay => Duration::days(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub interval: PriceInterval,
+    pub from: Option<NaiveDateTime>,
+    /// Defaults to now.
+    pub to: Option<NaiveDateTime>,
+}
+
+impl HistoryQuery {
+    /// Returns the requested time range, or `None` if it spans too many candles.
+    fn range(&self, now: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let interval = self.interval.duration();
+        let to = self.to.unwrap_or(now);
+        let from = self.from.unwrap_or(to - interval * DEFAULT_CANDLES);
+
+        let candles = (to - from).num_seconds() / interval.num_seconds();
+        (candles <= MAX_CANDLES).then_some((from, to))
+    }
+}
+
+/// Open, high, low and close ratios of a token during an interval.
+#[derive(Debug, Serialize)]
+pub struct Candle {
+    /// Start of the interval.
+    pub at: NaiveDateTime,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+}
+
+impl From<PriceCandleModel> for Candle {
+    fn from(model: PriceCandleModel) -> Self {
+        let price = |ratio: U256| Price(PairRatio(U256FD128::from(**ratio)));
+
+        Self {
+            at: model.at,
+            open: price(model.open),
+            high: price(model.high),
+            low: price(model.low),
+            close: price(model.close),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceHistory {
+    pub token: String,
+    pub interval: PriceInterval,
+    /// Candles from the oldest to the most recent, intervals without any price are skipped.
+    pub candles: Vec<Candle>,
+}
+
+impl PriceRoute {
+    pub(super) async fn get_history(
+        State(token_price_repository): State<Arc<TokenPriceRepository>>,
+        Path(token): Path<String>,
+        Query(query): Query<HistoryQuery>,
+    ) -> Result<Json<PriceHistory>, StatusCode> {
+        // Prices are stored by fixed hex address
+        let token = Felt::from_str(&token)
+            .map_err(|_| StatusCode::BAD_REQUEST)?
+            .to_fixed_hex_string();
+        let (from, to) = query
+            .range(Utc::now().naive_utc())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+
+        let candles = token_price_repository
+            .get_candles(&token, query.interval.duration().num_seconds(), from, to)
+            .await
+            .map_err(|err| {
+                error!("Error while fetching price history: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        Ok(Json(PriceHistory {
+            token,
+            interval: query.interval,
+            candles: candles.into_iter().map(Candle::from).collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let now = DateTime::from_timestamp(1_000_000, 0).unwrap().naive_utc();
+
+        let query = HistoryQuery::default();
+        assert_eq!(query.range(now), Some((now - Duration::hours(100), now)));
+
+        let query = HistoryQuery {
+            interval: PriceInterval::OneMinute,
+            from: Some(now - Duration::days(1)),
+            to: None,
+        };
+        assert_eq!(query.range(now), None);
+    }
+
+    #[test]
+    fn test_interval_deserialization() {
+        let query: HistoryQuery = serde_json::from_str(r#"{"interval": "15m"}"#).unwrap();
+        assert_eq!(query.interval, PriceInterval::FifteenMinutes);
+    }
+}
@@ -1,6 +1,13 @@
-use axum::{extract::State, routing::get, Json, Router};
-use ekubo::{contract::pool_price::PoolKey, price::PairRatio};
-use serde::Serialize;
+mod history;
+mod quote;
+
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use ekubo::{contract::pool_price::PoolKey, price::PairRatio, route::Hop};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use crate::{
@@ -21,12 +28,39 @@ impl Serialize for Price {
     }
 }
 
+/// Which ratio of a token is served.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceKind {
+    /// The last ratio read from the pools.
+    #[default]
+    Spot,
+    /// The time weighted average ratio, which cannot be moved by a single swap.
+    Twap,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PriceQuery {
+    #[serde(default)]
+    pub kind: PriceKind,
+}
+
 #[derive(Debug, Serialize)]
 pub struct TokenWithPrice {
     pub symbol: String,
     pub address: String,
+    pub name: Option<String>,
+    pub decimals: Option<u8>,
+    pub kind: PriceKind,
+    /// Amount of the token for one main token, in their smallest units.
     pub ratio: Option<Price>,
+    /// Amount of whole tokens for one whole main token, `None` if decimals are not known.
+    pub decimal_ratio: Option<f64>,
     pub best_pool: Option<PoolKey>,
+    /// The swaps from the main token used to price the token.
+    pub route: Vec<Hop>,
+    /// Total value locked of the thinnest pool of the route, in the main token.
+    pub liquidity: Option<f64>,
 }
 pub struct PriceRoute;
 
@@ -43,34 +77,52 @@ impl PriceRoute {
     }
 
     pub fn router(self) -> Router<AppState> {
-        Router::new().route("/", get(Self::get_price))
+        Router::new()
+            .route("/", get(Self::get_price))
+            .route("/{token}/history", get(Self::get_history))
+            .route("/{token}/quote", get(Self::get_quote))
     }
 
     #[allow(clippy::unused_async)] // required for axum
     async fn get_price(
         State(token_service): State<Arc<TokenService>>,
         State(ekubo_service): State<Arc<EkuboService>>,
+        Query(query): Query<PriceQuery>,
     ) -> Json<Vec<TokenWithPrice>> {
+        let main_decimals = token_service.main_token().decimals;
         let tokens = token_service
-            .tokens
+            .list()
             .iter()
             .map(|token| {
-                let ratio = ekubo_service.get_price_of(&token.address.to_fixed_hex_string());
+                let information = ekubo_service.get_price_of(&token.address.to_fixed_hex_string());
+
+                let (ratio, best_pool, route, liquidity) = match information {
+                    Some(information) => (
+                        match query.kind {
+                            PriceKind::Spot => Some(information.ratio),
+                            PriceKind::Twap => information.twap,
+                        },
+                        Some(information.pool),
+                        information.route,
+                        information.liquidity,
+                    ),
+                    None => (None, None, Vec::new(), None),
+                };
+                let decimal_ratio = ratio
+                    .as_ref()
+                    .and_then(|ratio| Some(ratio.to_decimal(main_decimals?, token.decimals?)));
 
-                if let Some(ratio) = ratio {
-                    TokenWithPrice {
-                        symbol: token.symbol.clone(),
-                        address: token.address.to_fixed_hex_string(),
-                        ratio: Some(Price(ratio.ratio)),
-                        best_pool: Some(ratio.pool),
-                    }
-                } else {
-                    TokenWithPrice {
-                        symbol: token.symbol.clone(),
-                        address: token.address.to_fixed_hex_string(),
-                        ratio: None,
-                        best_pool: None,
-                    }
+                TokenWithPrice {
+                    symbol: token.symbol.clone(),
+                    address: token.address.to_fixed_hex_string(),
+                    name: token.name.clone(),
+                    decimals: token.decimals,
+                    kind: query.kind,
+                    ratio: ratio.map(Price),
+                    decimal_ratio,
+                    best_pool,
+                    route,
+                    liquidity,
                 }
             })
             .collect();
@@ -0,0 +1,160 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use ekubo::{quote::Quote, route::Hop};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tracing::error;
+
+use crate::service::ekubo::EkuboService;
+
+use super::PriceRoute;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuoteQuery {
+    /// Amount of main token to swap, in its smallest unit.
+    pub amount: String,
+}
+
+/// Swap through one pool of the route. Amounts are decimal strings.
+#[derive(Debug, Serialize)]
+pub struct HopQuote {
+    #[serde(flatten)]
+    pub hop: Hop,
+    pub amount_in: String,
+    pub fee: String,
+    pub amount_out: String,
+    pub price_impact: f64,
+    /// Whether the price stays in the range where the liquidity of the pool is constant.
+    pub within_tick_range: bool,
+}
+
+/// Swap of the main token into a token, along the route of its price.
+///
+/// Quotes assume that the liquidity of each pool at its current price is constant for the whole
+/// swap, so they are only exact when `within_tick_range` is true.
+#[derive(Debug, Serialize)]
+pub struct SwapQuote {
+    pub token: String,
+    pub amount_in: String,
+    pub amount_out: String,
+    /// Combined price impact of the hops, fees excluded.
+    pub price_impact: f64,
+    /// Whether every hop stays in the range where the liquidity of its pool is constant, otherwise
+    /// the price impact may be underestimated.
+    pub within_tick_range: bool,
+    pub hops: Vec<HopQuote>,
+}
+
+impl SwapQuote {
+    fn new(token: String, amount_in: u128, quotes: Vec<(Hop, Quote)>) -> Self {
+        let amount_out = quotes
+            .last()
+            .map_or(amount_in, |(_, quote)| quote.amount_out);
+        let hops = quotes
+            .into_iter()
+            .map(|(hop, quote)| HopQuote {
+                hop,
+                amount_in: quote.amount_in.to_string(),
+                fee: quote.fee.to_string(),
+                amount_out: quote.amount_out.to_string(),
+                price_impact: quote.price_impact.into(),
+                within_tick_range: quote.within_tick_range,
+            })
+            .collect::<Vec<_>>();
+        let kept = hops
+            .iter()
+            .fold(1.0, |kept, hop| kept * (1.0 - hop.price_impact));
+
+        Self {
+            token,
+            amount_in: amount_in.to_string(),
+            amount_out: amount_out.to_string(),
+            price_impact: 1.0 - kept,
+            within_tick_range: hops.iter().all(|hop| hop.within_tick_range),
+            hops,
+        }
+    }
+}
+
+impl PriceRoute {
+    pub(super) async fn get_quote(
+        State(ekubo_service): State<Arc<EkuboService>>,
+        Path(token): Path<String>,
+        Query(query): Query<QuoteQuery>,
+    ) -> Result<Json<SwapQuote>, StatusCode> {
+        let token = Felt::from_str(&token).map_err(|_| StatusCode::BAD_REQUEST)?;
+        let amount = query
+            .amount
+            .parse::<u128>()
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+        let quotes = match ekubo_service.quote(token, amount).await {
+            Ok(Some(quotes)) => quotes,
+            Ok(None) => return Err(StatusCode::NOT_FOUND),
+            Err(ekubo::Error::InvalidSwap) => return Err(StatusCode::UNPROCESSABLE_ENTITY),
+            Err(err) => {
+                error!("Error while quoting a swap: {}", err);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        Ok(Json(SwapQuote::new(
+            token.to_fixed_hex_string(),
+            amount,
+            quotes,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ekubo::{contract::pool_price::PoolKey, math::u256fd128::U256FD128};
+
+    use super::*;
+
+    fn hop_quote(from: u8, to: u8, amount_in: u128, amount_out: u128) -> (Hop, Quote) {
+        let half = U256FD128::from_whole(1) / U256FD128::from_whole(2);
+
+        (
+            Hop {
+                from: Felt::from(from),
+                to: Felt::from(to),
+                pool: PoolKey {
+                    token0: Felt::from(from.min(to)),
+                    token1: Felt::from(from.max(to)),
+                    fee: 0,
+                    tick_spacing: 1,
+                    extension: Felt::ZERO,
+                },
+            },
+            Quote {
+                amount_in,
+                fee: 0,
+                amount_out,
+                price_impact: half,
+                sqrt_ratio_after: half,
+                within_tick_range: from == 1,
+            },
+        )
+    }
+
+    #[test]
+    fn test_swap_quote() {
+        let quote = SwapQuote::new(
+            "0x3".to_string(),
+            1000,
+            vec![hop_quote(1, 2, 1000, 500), hop_quote(2, 3, 500, 250)],
+        );
+
+        assert_eq!(quote.amount_out, "250");
+        assert_eq!(quote.hops.len(), 2);
+        assert!((quote.price_impact - 0.75).abs() < 1e-9);
+        assert!(quote.hops[0].within_tick_range);
+        assert!(!quote.within_tick_range);
+    }
+}
@@ -3,12 +3,25 @@ use std::sync::Arc;
 use axum::{extract::State, routing::get, Json, Router};
 use serde::Serialize;
 
-use crate::service::token::TokenService;
+use crate::{config, service::token::TokenService};
 
 #[derive(Debug, Serialize)]
 pub struct Token {
     pub symbol: String,
     pub address: String,
+    pub name: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+impl From<config::Token> for Token {
+    fn from(token: config::Token) -> Self {
+        Self {
+            symbol: token.symbol,
+            address: token.address.to_fixed_hex_string(),
+            name: token.name,
+            decimals: token.decimals,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -28,14 +41,7 @@ impl TokenRoute {
 
     #[allow(clippy::unused_async)] // required for axum
     async fn list_tokens(State(token_service): State<Arc<TokenService>>) -> Json<Vec<Token>> {
-        let tokens = token_service
-            .tokens
-            .iter()
-            .map(|token| Token {
-                symbol: token.symbol.clone(),
-                address: token.address.to_fixed_hex_string(),
-            })
-            .collect();
+        let tokens = token_service.list().into_iter().map(Token::from).collect();
         Json(tokens)
     }
 }
@@ -1,18 +1,42 @@
-use std::{collections::HashMap, str::FromStr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+};
 
 use anyhow::{Context, Result};
 use apalis::prelude::*;
 use apalis_cron::{CronContext, CronStream, Schedule};
 use arc_swap::ArcSwap;
-use chrono::Utc;
-use ekubo::{contract::pool_price::PoolKey, price::PairRatio, EkuboClient};
-use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient};
-use tracing::{error, info};
+use chaindata_models::models::TokenPriceModel;
+use chaindata_repository::TokenPriceRepository;
+use chrono::{Duration, NaiveDateTime, Utc};
+use ekubo::{
+    contract::pool_price::PoolKey,
+    math::u256fd128::U256FD128,
+    price::PairRatio,
+    quote::Quote,
+    route::{candidate_paths, Hop},
+    score::ScoringStrategy,
+    twap::twap,
+    EkuboClient,
+};
+use futures_util::{stream, StreamExt};
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt, U256 as RawU256},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient},
+};
+use tokio::sync::Mutex;
+use torii_ingester::u256::U256 as ToriiU256;
+use tracing::{error, info, warn};
 
 use crate::{config::Conf, monitoring::apalis::MonitoringLayer, worker::MonitorManager};
 
 use super::token::TokenService;
 
+/// Number of tokens priced at the same time.
+const CONCURRENT_ROUTES: usize = 8;
+
 #[derive(Debug, Default, Clone)]
 pub struct EkuboJob;
 
@@ -29,25 +53,85 @@ pub async fn update_ekubo(
 
 pub struct EkuboService {
     token_service: Arc<TokenService>,
+    token_price_repository: Arc<TokenPriceRepository>,
     exchange_rate: ArcSwap<PriceInformation>,
+    routing_tokens: Vec<Felt>,
+    scoring: ScoringStrategy,
+    twap_window: Duration,
     client: ekubo::EkuboClient<JsonRpcClient<HttpTransport>>,
+    /// Held for the whole of an update, and by every other change of the snapshot, so that they
+    /// do not overwrite each other.
+    updating: Mutex<()>,
 }
 
 #[derive(Debug, Clone)]
 pub struct EkuboTokenInformation {
     pub ratio: PairRatio,
+    /// The pool of the token the price was read from, the last hop of the route.
     pub pool: PoolKey,
+    /// The swaps from the main token to the token, empty when restored from the database.
+    pub route: Vec<Hop>,
+    /// Total value locked of the thinnest pool of the route, in the main token.
+    pub liquidity: Option<f64>,
+    /// Time weighted average of the ratio over the configured window.
+    pub twap: Option<PairRatio>,
+}
+
+impl EkuboTokenInformation {
+    /// Converts the information into a price to persist, `None` if the ratio is negative.
+    fn to_model(&self, token: String, at: NaiveDateTime) -> Option<TokenPriceModel> {
+        if self.ratio.is_negative() {
+            return None;
+        }
+
+        let [w0, w1, w2, w3] = self.ratio.raw().0;
+        let ratio = RawU256::from_words(
+            u128::from(w0) | u128::from(w1) << 64,
+            u128::from(w2) | u128::from(w3) << 64,
+        );
+
+        Some(TokenPriceModel {
+            token,
+            at,
+            pool_token0: self.pool.token0.to_fixed_hex_string(),
+            pool_token1: self.pool.token1.to_fixed_hex_string(),
+            pool_fee: self.pool.fee.to_string(),
+            pool_tick_spacing: self.pool.tick_spacing.into(),
+            pool_extension: self.pool.extension.to_fixed_hex_string(),
+            ratio: ToriiU256::from(ratio).into(),
+        })
+    }
+
+    /// Reads a persisted price, `None` if it is not valid.
+    fn from_model(model: &TokenPriceModel) -> Option<Self> {
+        Some(Self {
+            ratio: PairRatio(U256FD128::from(**model.ratio)),
+            pool: PoolKey {
+                token0: Felt::from_hex(&model.pool_token0).ok()?,
+                token1: Felt::from_hex(&model.pool_token1).ok()?,
+                fee: model.pool_fee.parse().ok()?,
+                tick_spacing: model.pool_tick_spacing.try_into().ok()?,
+                extension: Felt::from_hex(&model.pool_extension).ok()?,
+            },
+            route: Vec::new(),
+            liquidity: None,
+            twap: None,
+        })
+    }
 }
 
 #[derive(Default, Debug)]
 pub struct PriceInformation {
     inner: HashMap<String, EkuboTokenInformation>,
+    /// Block the prices were read at, `None` for persisted prices.
+    block_id: Option<BlockId>,
 }
 
 impl EkuboService {
     pub async fn new(
         config: &Conf,
         token_service: Arc<TokenService>,
+        token_price_repository: Arc<TokenPriceRepository>,
         monitor: &MonitorManager,
     ) -> Result<Arc<Self>> {
         let schedule =
@@ -57,14 +141,25 @@ impl EkuboService {
 
         let this = Arc::new(Self {
             token_service,
+            token_price_repository,
             exchange_rate: ArcSwap::new(Arc::new(PriceInformation::default())),
+            routing_tokens: config.ekubo.routing_tokens.clone(),
+            scoring: ScoringStrategy {
+                min_liquidity: config.ekubo.min_liquidity,
+                ..ScoringStrategy::default()
+            },
+            twap_window: Duration::seconds(config.ekubo.twap_window.try_into()?),
             client: EkuboClient::new(
                 config.ekubo.core_contract_address,
                 rpc_client,
                 config.ekubo.api_url.to_string(),
             ),
+            updating: Mutex::new(()),
         });
 
+        // Serve the last known prices until they are fetched again
+        this.load_persisted().await;
+
         // queue initial update
         info!("Initial price fetching...");
         this.update().await;
@@ -86,53 +181,266 @@ impl EkuboService {
         self.exchange_rate.load().inner.get(token).cloned()
     }
 
+    /// Prices the tokens again in the background, after a token was added.
+    ///
+    /// The update waits for the one in progress, if any.
+    pub fn refresh(self: &Arc<Self>) {
+        let this = self.clone();
+        tokio::spawn(async move { this.update().await });
+    }
+
+    /// Stops serving the price of a removed token.
+    ///
+    /// Waits for the update in progress, if any, so that it does not bring the price back.
+    pub async fn forget(&self, token: Felt) {
+        let _updating = self.updating.lock().await;
+        let token = token.to_fixed_hex_string();
+        self.exchange_rate.rcu(|price_info| PriceInformation {
+            block_id: price_info.block_id,
+            inner: price_info
+                .inner
+                .iter()
+                .filter(|(address, _)| **address != token)
+                .map(|(address, information)| (address.clone(), information.clone()))
+                .collect(),
+        });
+    }
+
+    /// Converts an amount of a token into the main token, using the last known price.
+    ///
+    /// Returns `None` if the price of the token is not known.
+    pub fn convert_to_main_token(&self, token: Felt, amount: RawU256) -> Option<RawU256> {
+        if token == self.token_service.main_token().address {
+            return Some(amount);
+        }
+
+        // The ratio is the amount of token for one main token
+        self.get_price_of(&token.to_fixed_hex_string())?
+            .ratio
+            .convert_back(amount)
+    }
+
+    /// Quotes a swap of `amount` main token into `token`, along the route of its last price and at
+    /// the block it was read at.
+    ///
+    /// Returns the quote of each hop, or `None` if the route of the token is not known.
+    ///
+    /// # Errors
+    /// Returns an error if a hop cannot be quoted, or if reading a pool fails.
+    pub async fn quote(
+        &self,
+        token: Felt,
+        amount: u128,
+    ) -> Result<Option<Vec<(Hop, Quote)>>, ekubo::Error> {
+        // The route and the block come from the same snapshot
+        let price_info = self.exchange_rate.load_full();
+        let route = match price_info.inner.get(&token.to_fixed_hex_string()) {
+            Some(information) if !information.route.is_empty() => information.route.clone(),
+            _ => return Ok(None),
+        };
+
+        // Every hop is quoted at the block of the last update, whose pools are cached. Quotes
+        // never read the block number, so that requests cannot add to the RPC load.
+        let block_id = price_info
+            .block_id
+            .unwrap_or(BlockId::Tag(BlockTag::Latest));
+
+        let mut amount = amount;
+        let mut quotes = Vec::with_capacity(route.len());
+        for hop in route {
+            let quote = self
+                .client
+                .quote_swap(&hop.pool, hop.from, amount, block_id)
+                .await?;
+            amount = quote.amount_out;
+            quotes.push((hop, quote));
+        }
+
+        Ok(Some(quotes))
+    }
+
+    /// Sets the time weighted average ratio of each token, from the persisted prices of the
+    /// window ending at `now`.
+    async fn compute_twaps(&self, price_info: &mut PriceInformation, now: NaiveDateTime) {
+        let from = now - self.twap_window;
+        let samples = match self.token_price_repository.get_samples(from, now).await {
+            Ok(samples) => samples,
+            Err(err) => {
+                error!("Failed to load the price samples: {}", err);
+                return;
+            }
+        };
+
+        let mut by_token: HashMap<String, Vec<(i64, PairRatio)>> = HashMap::new();
+        for sample in samples {
+            by_token.entry(sample.token).or_default().push((
+                sample.at.and_utc().timestamp(),
+                PairRatio(U256FD128::from(**sample.ratio)),
+            ));
+        }
+
+        for (token, information) in &mut price_info.inner {
+            information.twap = by_token.get(token).and_then(|samples| {
+                twap(
+                    samples,
+                    from.and_utc().timestamp(),
+                    now.and_utc().timestamp(),
+                )
+            });
+        }
+    }
+
+    /// Loads the last persisted price of each token.
+    async fn load_persisted(&self) {
+        let prices = match self.token_price_repository.get_latest_all().await {
+            Ok(prices) => prices,
+            Err(err) => {
+                error!("Failed to load the persisted prices: {}", err);
+                return;
+            }
+        };
+
+        let mut price_info = PriceInformation::default();
+        for price in prices {
+            match EkuboTokenInformation::from_model(&price) {
+                Some(information) => {
+                    price_info.inner.insert(price.token, information);
+                }
+                None => warn!("Ignoring the invalid persisted price of {}", price.token),
+            }
+        }
+
+        self.compute_twaps(&mut price_info, Utc::now().naive_utc())
+            .await;
+        info!("Loaded {} persisted prices", price_info.inner.len());
+        self.exchange_rate.swap(Arc::new(price_info));
+    }
+
     #[allow(clippy::missing_panics_doc)]
     /// Update the exchange rate information.
+    ///
+    /// When the price of a token cannot be fetched, its last known price is kept. Updates run one
+    /// at a time, each one starting from the snapshot of the previous one.
     pub async fn update(&self) {
+        let _updating = self.updating.lock().await;
         let main_token = self.token_service.main_token().address;
 
+        let previous = self.exchange_rate.load_full();
         let mut price_info = PriceInformation::default();
+        let keep_previous = |price_info: &mut PriceInformation, token: &Felt| {
+            let token = token.to_fixed_hex_string();
+            if let Some(information) = previous.inner.get(&token) {
+                price_info.inner.insert(token, information.clone());
+            }
+        };
+        let now = Utc::now().naive_utc();
+        let mut fetched = Vec::new();
+
+        // Every price of the snapshot is read at this block, and each pool only once. It is passed
+        // to every read, as quotes may sync a newer block in the meantime.
+        let block_id = match self.client.sync_block().await {
+            Ok(block_number) => BlockId::Number(block_number),
+            Err(err) => {
+                warn!(
+                    "Failed to read the block number, prices are read at the latest block: {}",
+                    err
+                );
+                BlockId::Tag(BlockTag::Latest)
+            }
+        };
+
+        let tokens = self
+            .token_service
+            .list()
+            .into_iter()
+            .filter(|token| token.address != main_token)
+            .collect::<Vec<_>>();
+        // Tokens without a pool against the main token are priced through the other tokens
+        let intermediates = self
+            .routing_tokens
+            .iter()
+            .copied()
+            .chain(tokens.iter().map(|token| token.address))
+            .collect::<Vec<_>>();
+
+        // The prices of every pool the routes may go through are read in a single batch, the
+        // routes are then found from the cache of the block. Without a block, nothing is cached.
+        if let BlockId::Number(_) = block_id {
+            let pairs = tokens
+                .iter()
+                .flat_map(|token| candidate_paths(main_token, token.address, &intermediates))
+                .flat_map(|path| {
+                    path.windows(2)
+                        .map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1])))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            if let Err(err) = self.client.prefetch(&pairs, block_id).await {
+                warn!(
+                    "Failed to read the prices of the snapshot at once, they are read per route: {}",
+                    err
+                );
+            }
+        }
 
-        'token_loop: for token in self.token_service.list() {
-            let pool = match self.client.get_pools(main_token, token.address).await {
-                Ok(vec) if !vec.is_empty() => vec.into_iter().next().unwrap(), // safe because of the bounds check
-                Ok(_) => {
-                    // No pools found for token, go to the next one.
-                    continue 'token_loop;
+        let intermediates = &intermediates;
+        let routes = stream::iter(tokens)
+            .map(|token| async move {
+                let route = self
+                    .client
+                    .get_route(
+                        main_token,
+                        token.address,
+                        intermediates,
+                        &self.scoring,
+                        block_id,
+                    )
+                    .await;
+                (token, route)
+            })
+            .buffer_unordered(CONCURRENT_ROUTES)
+            .collect::<Vec<_>>()
+            .await;
+
+        for (token, route) in routes {
+            let route = match route {
+                Ok(route) => route,
+                Err(ekubo::Error::PoolNotFound) => {
+                    // No route with enough liquidity for token, go to the next one.
+                    continue;
                 }
                 Err(err) => {
                     error!(
-                        "Failed to fetch pool for token {}: {:#?}",
+                        "Failed to fetch price for token {}: {:#?}",
                         token.address, err
                     );
-                    continue 'token_loop;
+                    keep_previous(&mut price_info, &token.address);
+                    continue;
                 }
             };
 
-            let price = match self.client.read_pool_price(&pool.key).await {
-                Ok(price) => price,
-                Err(err) => {
-                    error!("Failed to fetch price for pool {}: {:#?}", pool.key, err);
-                    continue 'token_loop;
-                }
+            let information = EkuboTokenInformation {
+                pool: route.hops.last().expect("routes have a hop").pool.clone(),
+                ratio: route.ratio,
+                route: route.hops,
+                liquidity: Some(route.liquidity),
+                twap: None,
             };
+            fetched.extend(information.to_model(token.address.to_fixed_hex_string(), now));
+            price_info
+                .inner
+                .insert(token.address.to_fixed_hex_string(), information);
+        }
 
-            let price = if main_token < token.address {
-                price
-            } else {
-                price.inverse()
-            };
+        info!("Finished ekubo update at block {:?}!", block_id);
+        price_info.block_id = Some(block_id);
 
-            price_info.inner.insert(
-                token.address.to_fixed_hex_string(),
-                EkuboTokenInformation {
-                    pool: pool.key,
-                    ratio: price,
-                },
-            );
+        if let Err(err) = self.token_price_repository.save_all(&fetched).await {
+            error!("Failed to persist the prices: {}", err);
         }
-
-        info!("Finished ekubo update!");
+        self.compute_twaps(&mut price_info, now).await;
 
         // Once everything is done, update the exchange rate
         self.exchange_rate.swap(Arc::new(price_info));
@@ -0,0 +1,318 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use anyhow::{Context, Result};
+use apalis::prelude::*;
+use apalis_cron::{CronContext, CronStream, Schedule};
+use chaindata_models::{
+    models::{LeaderboardEntryModel, LeaderboardKind},
+    shared::U256,
+};
+use chaindata_repository::{LandRepository, LandStakeRepository, LeaderboardRepository};
+use chrono::{Duration, NaiveDateTime, Utc};
+use sqlx::types::BigDecimal;
+use starknet::core::types::{Felt, U256 as RawU256};
+use torii_ingester::u256::U256 as ToriiU256;
+use tracing::{error, info, warn};
+
+use crate::{monitoring::apalis::MonitoringLayer, worker::MonitorManager};
+
+use super::{
+    ekubo::EkuboService,
+    valuation::{Amount, ValuationService},
+};
+
+/// Number of players kept in each leaderboard snapshot.
+pub const LEADERBOARD_SIZE: usize = 100;
+
+/// Age after which only the last snapshot of each day is kept.
+const FULL_HISTORY: Duration = Duration::days(1);
+
+#[derive(Debug, Default, Clone)]
+pub struct LeaderboardJob;
+
+pub async fn update_leaderboards(
+    _: LeaderboardJob,
+    _ctx: CronContext<Utc>,
+    leaderboard: Data<Arc<LeaderboardService>>,
+    task_id: TaskId,
+) {
+    info!("Updating leaderboards! {}", task_id);
+
+    leaderboard.update().await;
+}
+
+/// `LeaderboardService` periodically ranks the players, and saves a snapshot of each leaderboard.
+pub struct LeaderboardService {
+    land_repository: Arc<LandRepository>,
+    land_stake_repository: Arc<LandStakeRepository>,
+    leaderboard_repository: Arc<LeaderboardRepository>,
+    ekubo_service: Arc<EkuboService>,
+    valuation_service: Arc<ValuationService>,
+}
+
+impl LeaderboardService {
+    pub fn new(
+        land_repository: Arc<LandRepository>,
+        land_stake_repository: Arc<LandStakeRepository>,
+        leaderboard_repository: Arc<LeaderboardRepository>,
+        ekubo_service: Arc<EkuboService>,
+        valuation_service: Arc<ValuationService>,
+        monitor: &MonitorManager,
+    ) -> Result<Arc<Self>> {
+        let schedule =
+            Schedule::from_str("0 0/15 * * * *").with_context(|| "Could not parse Schedule")?;
+
+        let this = Arc::new(Self {
+            land_repository,
+            land_stake_repository,
+            leaderboard_repository,
+            ekubo_service,
+            valuation_service,
+        });
+
+        let worker = WorkerBuilder::new("leaderboard-update")
+            .enable_tracing()
+            .concurrency(1)
+            .layer(MonitoringLayer::new("leaderboard-update"))
+            .data(this.clone())
+            .backend(CronStream::new_with_timezone(schedule, Utc))
+            .build_fn(update_leaderboards);
+
+        monitor.register(move |mon| mon.register(worker));
+
+        Ok(this)
+    }
+
+    /// Computes and saves a new snapshot of every leaderboard, and thins out the old ones.
+    pub async fn update(&self) {
+        let computed_at = Utc::now().naive_utc();
+
+        for kind in LeaderboardKind::ALL {
+            let scores = match self.compute(kind, computed_at).await {
+                Ok(scores) => scores,
+                Err(err) => {
+                    error!("Failed to compute the {:?} leaderboard: {:#}", kind, err);
+                    continue;
+                }
+            };
+
+            let entries = rank(kind, computed_at, scores);
+            if let Err(err) = self.leaderboard_repository.save(&entries).await {
+                error!("Failed to save the {:?} leaderboard: {}", kind, err);
+                continue;
+            }
+
+            // Snapshots are taken every 15 minutes, older ones are only kept daily
+            if let Err(err) = self
+                .leaderboard_repository
+                .thin_out(kind, computed_at - FULL_HISTORY)
+                .await
+            {
+                error!("Failed to thin out the {:?} leaderboard: {}", kind, err);
+            }
+        }
+    }
+
+    async fn compute(
+        &self,
+        kind: LeaderboardKind,
+        at: NaiveDateTime,
+    ) -> Result<HashMap<Felt, BigDecimal>> {
+        match kind {
+            LeaderboardKind::LandsHeld => {
+                let lands = self.land_repository.get_all_at_time(at).await?;
+                Ok(sum_by_address(
+                    lands
+                        .iter()
+                        .map(|land| (land.owner.as_str(), BigDecimal::from(1))),
+                ))
+            }
+            LeaderboardKind::LandValue => self.compute_land_value(at).await,
+            LeaderboardKind::LandsNuked => {
+                let counts = self.leaderboard_repository.get_nuked_counts().await?;
+                Ok(sum_by_address(counts.iter().map(|(address, count)| {
+                    (address.as_str(), BigDecimal::from(*count))
+                })))
+            }
+            LeaderboardKind::AuctionsWon => {
+                let counts = self.leaderboard_repository.get_auction_wins().await?;
+                Ok(sum_by_address(counts.iter().map(|(address, count)| {
+                    (address.as_str(), BigDecimal::from(*count))
+                })))
+            }
+            LeaderboardKind::RealizedProfit => self.compute_realized_profit().await,
+        }
+    }
+
+    async fn compute_land_value(&self, at: NaiveDateTime) -> Result<HashMap<Felt, BigDecimal>> {
+        let lands = self.land_repository.get_all_at_time(at).await?;
+        let stakes: HashMap<u64, RawU256> = self
+            .land_stake_repository
+            .get_all_at_time(at)
+            .await?
+            .into_iter()
+            .map(|stake| ((*stake.location).0, **stake.amount))
+            .collect();
+
+        // Lands in a token without a price are not counted
+        let values = lands.iter().filter_map(|land| {
+            let token = Felt::from_str(&land.token_used).ok()?;
+            let stake = stakes
+                .get(&(*land.location).0)
+                .copied()
+                .unwrap_or(RawU256::from(0u8));
+            let sell_price = self
+                .ekubo_service
+                .convert_to_main_token(token, **land.sell_price)?;
+            let stake = self.ekubo_service.convert_to_main_token(token, stake)?;
+            let Some(value) = ToriiU256::from(sell_price).checked_add(ToriiU256::from(stake))
+            else {
+                warn!(
+                    "Value of the land at {} overflows, skipping it",
+                    (*land.location).0
+                );
+                return None;
+            };
+            Some((land.owner.as_str(), to_decimal(*value)))
+        });
+
+        Ok(sum_by_address(values))
+    }
+
+    async fn compute_realized_profit(&self) -> Result<HashMap<Felt, BigDecimal>> {
+        let sales = self.leaderboard_repository.get_land_sales().await?;
+        let main_token = self.valuation_service.main_token();
+
+        // Sell prices then buy prices, each valued at the price of its token at that time
+        let amounts: Vec<Option<Amount>> = sales
+            .iter()
+            .map(|sale| {
+                Some(Amount {
+                    token: Felt::from_str(&sale.sell_token).ok()?,
+                    amount: **sale.sell_price,
+                    at: sale.sold_at,
+                })
+            })
+            .chain(sales.iter().map(|sale| {
+                Some(Amount {
+                    // Auctions are paid in the main token
+                    token: match &sale.buy_token {
+                        Some(buy_token) => Felt::from_str(buy_token).ok()?,
+                        None => main_token,
+                    },
+                    amount: **sale.buy_price,
+                    at: sale.bought_at,
+                })
+            }))
+            .collect();
+        let mut sell_values = self
+            .valuation_service
+            .to_main_token_sparse(&amounts)
+            .await?;
+        let buy_values = sell_values.split_off(sales.len());
+
+        // Sales without a known price are not counted
+        let profits = sales
+            .iter()
+            .zip(sell_values.into_iter().zip(buy_values))
+            .filter_map(|(sale, values)| match values {
+                (Some(sell_value), Some(buy_value)) => Some((
+                    sale.seller.as_str(),
+                    BigDecimal::from(sell_value) - BigDecimal::from(buy_value),
+                )),
+                _ => None,
+            });
+
+        Ok(sum_by_address(profits))
+    }
+}
+
+fn to_decimal(amount: RawU256) -> BigDecimal {
+    U256::from(ToriiU256::from(amount)).into()
+}
+
+/// Sums the scores of each address, merging the different formats of the same address.
+///
+/// Invalid addresses, and the zero address (lands without owner), are ignored.
+fn sum_by_address<'a>(
+    scores: impl Iterator<Item = (&'a str, BigDecimal)>,
+) -> HashMap<Felt, BigDecimal> {
+    let mut sums: HashMap<Felt, BigDecimal> = HashMap::new();
+    for (address, score) in scores {
+        let Ok(address) = Felt::from_str(address) else {
+            continue;
+        };
+        if address == Felt::ZERO {
+            continue;
+        }
+        *sums.entry(address).or_default() += score;
+    }
+    sums
+}
+
+/// Keeps the best [`LEADERBOARD_SIZE`] players, ordered by score then address.
+fn rank(
+    kind: LeaderboardKind,
+    computed_at: NaiveDateTime,
+    scores: HashMap<Felt, BigDecimal>,
+) -> Vec<LeaderboardEntryModel> {
+    let mut scores: Vec<(Felt, BigDecimal)> = scores.into_iter().collect();
+    scores.sort_by(|(address_a, score_a), (address_b, score_b)| {
+        score_b.cmp(score_a).then_with(|| address_a.cmp(address_b))
+    });
+
+    scores
+        .into_iter()
+        .take(LEADERBOARD_SIZE)
+        .zip(1..)
+        .map(|((address, score), rank)| LeaderboardEntryModel {
+            kind,
+            computed_at,
+            rank,
+            address: format!("{address:#x}"),
+            score,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sum_by_address() {
+        let sums = sum_by_address(
+            [
+                ("10", BigDecimal::from(1)),
+                ("0xa", BigDecimal::from(2)),
+                ("0x0", BigDecimal::from(5)),
+                ("not an address", BigDecimal::from(5)),
+                ("0xb", BigDecimal::from(-3)),
+            ]
+            .into_iter(),
+        );
+
+        assert_eq!(sums.len(), 2);
+        // Decimal and hexadecimal formats are the same address
+        assert_eq!(sums[&Felt::from(10u8)], BigDecimal::from(3));
+        assert_eq!(sums[&Felt::from(11u8)], BigDecimal::from(-3));
+    }
+
+    #[test]
+    fn test_rank() {
+        let at = NaiveDateTime::default();
+        let mut scores: HashMap<Felt, BigDecimal> = (0..200u32)
+            .map(|i| (Felt::from(i + 1), BigDecimal::from(i)))
+            .collect();
+        // Same score as the best player
+        scores.insert(Felt::from(1000u32), BigDecimal::from(199));
+
+        let entries = rank(LeaderboardKind::LandsHeld, at, scores);
+        assert_eq!(entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(entries[0].rank, 1);
+        assert_eq!(entries[0].address, "0xc8");
+        assert_eq!(entries[1].address, "0x3e8");
+        assert_eq!(entries[99].rank, 100);
+        assert_eq!(entries[99].score, BigDecimal::from(101));
+    }
+}
@@ -1,2 +1,4 @@
 pub mod ekubo;
+pub mod leaderboard;
 pub mod token;
+pub mod valuation;
@@ -1,33 +1,256 @@
+use std::sync::Arc;
+
 use anyhow::{anyhow, Result};
+use arc_swap::ArcSwap;
+use chaindata_models::models::TokenModel;
+use chaindata_repository::TokenRepository;
+use chrono::Utc;
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, Felt, FunctionCall},
+        utils::parse_cairo_short_string,
+    },
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use thiserror::Error;
+use tracing::{info, warn};
 
 use crate::config::{Conf, Token};
 
+/// Why the token list cannot be changed.
+#[derive(Debug, Error)]
+pub enum TokenError {
+    #[error("Token not found")]
+    NotFound,
+    /// The main token prices every other token, it cannot be changed while running.
+    #[error("The main token cannot be changed")]
+    MainToken,
+    #[error("Failed to store the tokens: {0}")]
+    Storage(#[from] chaindata_repository::Error),
+}
+
 pub struct TokenService {
-    pub tokens: Vec<Token>,
-    pub main_token: Token,
+    tokens: ArcSwap<Vec<Token>>,
+    main_token: Token,
+    token_repository: Arc<TokenRepository>,
+    provider: JsonRpcClient<HttpTransport>,
 }
 
 impl TokenService {
-    pub fn new(config: &Conf) -> Result<Self> {
-        let main_token = config
-            .token
+    /// Creates the service from the stored tokens.
+    ///
+    /// The configured tokens are stored on the first start, the list is then managed through the
+    /// admin API. The name and decimals of a token are read from its ERC20 contract when it is
+    /// stored, a token whose metadata could not be read has to be added again to retry.
+    pub async fn new(
+        config: &Conf,
+        token_repository: Arc<TokenRepository>,
+        provider: JsonRpcClient<HttpTransport>,
+    ) -> Result<Self> {
+        let mut stored = token_repository.get_all().await?;
+        if stored.is_empty() {
+            info!("Storing the {} configured tokens", config.token.len());
+            for token in &config.token {
+                let mut token = token.clone();
+                fetch_metadata(&provider, &mut token).await;
+                token_repository.save(&token.to_model()).await?;
+            }
+            stored = token_repository.get_all().await?;
+        }
+
+        let tokens = stored
+            .iter()
+            .map(Token::from_model)
+            .collect::<Result<Vec<_>>>()?;
+
+        let main_token = tokens
             .iter()
             .find(|e| e.symbol == config.default_token)
-            .ok_or_else(|| anyhow!("Impossible to find token!"))?;
+            .ok_or_else(|| anyhow!("Impossible to find token!"))?
+            .clone();
 
         Ok(TokenService {
-            tokens: config.token.clone(),
-            main_token: main_token.clone(),
+            tokens: ArcSwap::from_pointee(tokens),
+            main_token,
+            token_repository,
+            provider,
         })
     }
 
     #[must_use]
     pub fn list(&self) -> Vec<Token> {
-        self.tokens.clone()
+        self.tokens.load().to_vec()
     }
 
     #[must_use]
     pub fn main_token(&self) -> &Token {
         &self.main_token
     }
+
+    /// Adds a token, or replaces the token with the same address.
+    ///
+    /// Its name and decimals are read from its contract when not given.
+    pub async fn add(&self, mut token: Token) -> Result<Token, TokenError> {
+        if token.address == self.main_token.address {
+            return Err(TokenError::MainToken);
+        }
+
+        fetch_metadata(&self.provider, &mut token).await;
+        self.token_repository.save(&token.to_model()).await?;
+
+        self.tokens.rcu(|tokens| {
+            let mut tokens = tokens.to_vec();
+            match tokens.iter_mut().find(|e| e.address == token.address) {
+                Some(known) => known.clone_from(&token),
+                None => tokens.push(token.clone()),
+            }
+            tokens
+        });
+        info!("Added token {} ({:#x})", token.symbol, token.address);
+
+        Ok(token)
+    }
+
+    /// Removes a token, which is not priced anymore.
+    pub async fn remove(&self, address: Felt) -> Result<(), TokenError> {
+        if address == self.main_token.address {
+            return Err(TokenError::MainToken);
+        }
+
+        let deleted = self
+            .token_repository
+            .delete(&address.to_fixed_hex_string())
+            .await?;
+        if !deleted {
+            return Err(TokenError::NotFound);
+        }
+
+        self.tokens.rcu(|tokens| {
+            tokens
+                .iter()
+                .filter(|e| e.address != address)
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+        info!("Removed token {:#x}", address);
+
+        Ok(())
+    }
+}
+
+impl Token {
+    fn to_model(&self) -> TokenModel {
+        TokenModel {
+            address: self.address.to_fixed_hex_string(),
+            symbol: self.symbol.clone(),
+            name: self.name.clone(),
+            decimals: self.decimals.map(i16::from),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn from_model(model: &TokenModel) -> Result<Self> {
+        Ok(Self {
+            symbol: model.symbol.clone(),
+            address: Felt::from_hex(&model.address)
+                .map_err(|_| anyhow!("Invalid token address {}", model.address))?,
+            name: model.name.clone(),
+            decimals: model.decimals.map(u8::try_from).transpose()?,
+        })
+    }
+}
+
+async fn call<P: Provider + Sync>(
+    provider: &P,
+    address: Felt,
+    entry_point_selector: Felt,
+) -> Option<Vec<Felt>> {
+    provider
+        .call(
+            FunctionCall {
+                contract_address: address,
+                entry_point_selector,
+                calldata: Vec::new(),
+            },
+            BlockId::Tag(BlockTag::Latest),
+        )
+        .await
+        .inspect_err(|err| {
+            warn!(
+                "Failed to read the metadata of token {:#x}: {}",
+                address, err
+            )
+        })
+        .ok()
+}
+
+/// Reads the missing name and decimals of a token from its contract.
+async fn fetch_metadata<P: Provider + Sync>(provider: &P, token: &mut Token) {
+    if token.decimals.is_none() {
+        token.decimals = call(provider, token.address, selector!("decimals"))
+            .await
+            .and_then(|response| u8::try_from(*response.first()?).ok());
+    }
+
+    if token.name.is_none() {
+        token.name = call(provider, token.address, selector!("name"))
+            .await
+            .and_then(|response| decode_name(&response));
+    }
+}
+
+/// Decodes the name of an ERC20, either a short string or a `ByteArray`.
+fn decode_name(response: &[Felt]) -> Option<String> {
+    let [words, rest @ ..] = response else {
+        return None;
+    };
+    if rest.is_empty() {
+        return parse_cairo_short_string(words).ok();
+    }
+
+    // A `ByteArray` has full words of 31 bytes, then a pending word and its length
+    let words = usize::try_from(*words).ok()?;
+    let (full_words, [pending_word, pending_len]) = rest.split_at_checked(words)? else {
+        return None;
+    };
+    let pending_len = usize::try_from(*pending_len).ok().filter(|len| *len < 31)?;
+
+    let mut bytes = Vec::with_capacity(words * 31 + pending_len);
+    for word in full_words {
+        bytes.extend_from_slice(&word.to_bytes_be()[1..]);
+    }
+    bytes.extend_from_slice(&pending_word.to_bytes_be()[32 - pending_len..]);
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::utils::cairo_short_string_to_felt;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_name() {
+        assert_eq!(
+            decode_name(&[cairo_short_string_to_felt("Ether").unwrap()]),
+            Some("Ether".to_string())
+        );
+
+        let long = "A token with a name longer than 31 bytes";
+        let (full, pending) = long.split_at(31);
+        assert_eq!(
+            decode_name(&[
+                Felt::ONE,
+                Felt::from_bytes_be_slice(full.as_bytes()),
+                Felt::from_bytes_be_slice(pending.as_bytes()),
+                Felt::from(pending.len()),
+            ]),
+            Some(long.to_string())
+        );
+
+        assert_eq!(decode_name(&[]), None);
+        assert_eq!(decode_name(&[Felt::TWO, Felt::ONE]), None);
+    }
 }
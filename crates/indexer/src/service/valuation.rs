@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use chaindata_models::shared::U256;
+use chaindata_repository::TokenPriceRepository;
+use chrono::NaiveDateTime;
+use ekubo::{math::u256fd128::U256FD128, price::PairRatio};
+use starknet::core::types::{Felt, U256 as RawU256};
+use torii_ingester::u256::U256 as ToriiU256;
+
+use super::token::TokenService;
+
+/// An amount of a token at a given time.
+#[derive(Debug, Clone)]
+pub struct Amount {
+    pub token: Felt,
+    pub amount: RawU256,
+    pub at: NaiveDateTime,
+}
+
+/// `ValuationService` converts amounts into the main token, at the price the token had at the
+/// time of the amount, using the persisted price history.
+pub struct ValuationService {
+    token_service: Arc<TokenService>,
+    token_price_repository: Arc<TokenPriceRepository>,
+}
+
+impl ValuationService {
+    #[must_use]
+    pub fn new(
+        token_service: Arc<TokenService>,
+        token_price_repository: Arc<TokenPriceRepository>,
+    ) -> Self {
+        Self {
+            token_service,
+            token_price_repository,
+        }
+    }
+
+    /// Address of the token in which the amounts are converted.
+    #[must_use]
+    pub fn main_token(&self) -> Felt {
+        self.token_service.main_token().address
+    }
+
+    /// Converts each amount into the main token, in the same order as the input.
+    ///
+    /// An amount is `None` when no price of its token was known at its time.
+    ///
+    /// # Errors
+    /// Returns an error if the prices could not be fetched.
+    pub async fn to_main_token(
+        &self,
+        amounts: &[Amount],
+    ) -> Result<Vec<Option<U256>>, sqlx::Error> {
+        let main_token = self.main_token();
+
+        // Only look up the prices of the other tokens
+        let (tokens, at): (Vec<String>, Vec<NaiveDateTime>) = amounts
+            .iter()
+            .filter(|amount| amount.token != main_token)
+            .map(|amount| (amount.token.to_fixed_hex_string(), amount.at))
+            .unzip();
+        let mut ratios = self
+            .token_price_repository
+            .get_ratios_at(&tokens, &at)
+            .await?
+            .into_iter();
+
+        Ok(amounts
+            .iter()
+            .map(|amount| {
+                if amount.token == main_token {
+                    return Some(to_shared(amount.amount));
+                }
+
+                // The ratio is the amount of token for one main token
+                let ratio = PairRatio(U256FD128::from(**ratios.next()??));
+                ratio.convert_back(amount.amount).map(to_shared)
+            })
+            .collect())
+    }
+
+    /// Same as [`Self::to_main_token`], skipping the missing amounts.
+    ///
+    /// # Errors
+    /// Returns an error if the prices could not be fetched.
+    pub async fn to_main_token_sparse(
+        &self,
+        amounts: &[Option<Amount>],
+    ) -> Result<Vec<Option<U256>>, sqlx::Error> {
+        let existing: Vec<Amount> = amounts.iter().flatten().cloned().collect();
+        let mut values = self.to_main_token(&existing).await?.into_iter();
+
+        Ok(amounts
+            .iter()
+            .map(|amount| amount.as_ref().and_then(|_| values.next().flatten()))
+            .collect())
+    }
+}
+
+fn to_shared(amount: RawU256) -> U256 {
+    ToriiU256::from(amount).into()
+}
@@ -1,27 +1,53 @@
 use std::sync::Arc;
 
 use axum::extract::FromRef;
-use chaindata_repository::LandRepository;
+use chaindata_repository::{
+    AuctionRepository, EventRepository, LandRepository, LandStakeRepository, LeaderboardRepository,
+    TokenPriceRepository,
+};
+use chaindata_service::ChainDataService;
 
-use crate::service::{ekubo::EkuboService, token::TokenService};
+use crate::service::{ekubo::EkuboService, token::TokenService, valuation::ValuationService};
 
 #[derive(Clone)]
 pub struct AppState {
     pub token_service: Arc<TokenService>,
     pub ekubo_service: Arc<EkuboService>,
+    pub valuation_service: Arc<ValuationService>,
     pub land_repository: Arc<LandRepository>,
+    pub land_stake_repository: Arc<LandStakeRepository>,
+    pub auction_repository: Arc<AuctionRepository>,
+    pub event_repository: Arc<EventRepository>,
+    pub leaderboard_repository: Arc<LeaderboardRepository>,
+    pub token_price_repository: Arc<TokenPriceRepository>,
+    pub chaindata_service: Arc<ChainDataService>,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         token_service: Arc<TokenService>,
         ekubo_service: Arc<EkuboService>,
+        valuation_service: Arc<ValuationService>,
         land_repository: Arc<LandRepository>,
+        land_stake_repository: Arc<LandStakeRepository>,
+        auction_repository: Arc<AuctionRepository>,
+        event_repository: Arc<EventRepository>,
+        leaderboard_repository: Arc<LeaderboardRepository>,
+        token_price_repository: Arc<TokenPriceRepository>,
+        chaindata_service: Arc<ChainDataService>,
     ) -> Self {
         Self {
             token_service,
             ekubo_service,
+            valuation_service,
             land_repository,
+            land_stake_repository,
+            auction_repository,
+            event_repository,
+            leaderboard_repository,
+            token_price_repository,
+            chaindata_service,
         }
     }
 }
@@ -38,8 +64,50 @@ impl FromRef<AppState> for Arc<EkuboService> {
     }
 }
 
+impl FromRef<AppState> for Arc<ValuationService> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.valuation_service.clone()
+    }
+}
+
 impl FromRef<AppState> for Arc<LandRepository> {
     fn from_ref(app_state: &AppState) -> Self {
         app_state.land_repository.clone()
     }
 }
+
+impl FromRef<AppState> for Arc<LandStakeRepository> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.land_stake_repository.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<AuctionRepository> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.auction_repository.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<EventRepository> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.event_repository.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<LeaderboardRepository> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.leaderboard_repository.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<TokenPriceRepository> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.token_price_repository.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ChainDataService> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.chaindata_service.clone()
+    }
+}
@@ -0,0 +1,5 @@
+CREATE TABLE sync_cursor (
+    stream TEXT PRIMARY KEY,
+    last_event_id TEXT NOT NULL,
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
@@ -0,0 +1,9 @@
+CREATE TABLE failed_event (
+    stream TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    data TEXT NOT NULL,
+    error TEXT NOT NULL,
+    failed_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    PRIMARY KEY (stream, event_id)
+);
@@ -0,0 +1,16 @@
+CREATE TYPE leaderboard_kind AS ENUM (
+    'lands_held',
+    'land_value',
+    'lands_nuked',
+    'auctions_won',
+    'realized_profit'
+);
+
+CREATE TABLE leaderboard_entry (
+    kind leaderboard_kind NOT NULL,
+    computed_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    rank INT4 NOT NULL,
+    address TEXT NOT NULL,
+    score NUMERIC NOT NULL,
+    PRIMARY KEY (kind, computed_at, rank)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE token_price (
+    token TEXT NOT NULL,
+    at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    pool_token0 TEXT NOT NULL,
+    pool_token1 TEXT NOT NULL,
+    pool_fee TEXT NOT NULL,
+    pool_tick_spacing INT8 NOT NULL,
+    pool_extension TEXT NOT NULL,
+    -- Raw fixed point ratio, with 128 decimal bits
+    ratio uint_256 NOT NULL,
+    PRIMARY KEY (token, at)
+);
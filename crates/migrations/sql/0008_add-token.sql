@@ -0,0 +1,8 @@
+CREATE TABLE token (
+    -- Fixed hex string of the contract address
+    address TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    name TEXT,
+    decimals INT2,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
@@ -0,0 +1,24 @@
+//! Game constants, mirroring `contracts/src/consts.cairo`.
+//!
+//! Types are kept identical to the contract ones, except for `u256` constants that are stored
+//! as `u128` (they all fit) to be usable in a const context.
+
+pub const GRID_WIDTH: u16 = 64;
+pub const TAX_RATE: u16 = 2;
+pub const BASE_TIME: u16 = 3600;
+pub const PRICE_DECREASE_RATE: u16 = 2;
+pub const TIME_SPEED: u32 = 5;
+pub const DECAY_RATE: u16 = 200;
+pub const DECIMALS_FACTOR: u128 = 1_000_000_000_000_000_000;
+pub const FLOOR_PRICE: u128 = DECIMALS_FACTOR / 10;
+pub const MIN_AUCTION_PRICE: u128 = 500 * DECIMALS_FACTOR;
+pub const CENTER_LOCATION: u16 = 2080;
+
+/// One week in seconds
+pub const AUCTION_DURATION: u32 = 7 * 24 * 60 * 60;
+pub const SCALING_FACTOR: u8 = 50;
+/// 10 minutes IRL, already multiplied by the time speed
+pub const LINEAR_DECAY_TIME: u16 = 10 * 60 * 20;
+/// 90% of the price or 9/10
+pub const DROP_RATE: u8 = 90;
+pub const RATE_DENOMINATOR: u8 = 100;
@@ -0,0 +1,164 @@
+//! Port of `contracts/src/helpers/taxes.cairo`.
+//!
+//! The contract reads the current time from the block timestamp, here it needs to be given
+//! explicitly as a unix timestamp.
+
+use starknet::core::types::U256 as RawU256;
+use torii_ingester::u256::U256;
+
+use crate::{
+    consts::{BASE_TIME, TAX_RATE, TIME_SPEED},
+    models::{Land, LandStake},
+};
+
+/// Taxes that accumulated for each neighbor since the last payment of the stake.
+#[must_use]
+pub fn get_taxes_per_neighbor(land: &Land, land_stake: &LandStake, current_time: u64) -> U256 {
+    let elapsed_time = current_time.saturating_sub(land_stake.last_pay_time);
+
+    let tax_rate_per_neighbor = *get_tax_rate_per_neighbor(land);
+
+    (tax_rate_per_neighbor * RawU256::from(elapsed_time) / RawU256::from(BASE_TIME)).into()
+}
+
+/// Taxes paid to each neighbor every `BASE_TIME`, discounted by the level of the land.
+#[must_use]
+pub fn get_tax_rate_per_neighbor(land: &Land) -> U256 {
+    let max_n = land.location.max_neighbors();
+    if max_n == 0 {
+        return U256::from(0u8);
+    }
+
+    let discount_for_level = land.level.discount();
+    let base_tax_rate = *land.sell_price * RawU256::from(TAX_RATE) * RawU256::from(TIME_SPEED)
+        / (RawU256::from(max_n) * RawU256::from(100u8));
+
+    let discounted_tax_rate = if discount_for_level > 0 {
+        base_tax_rate * RawU256::from(100 - discount_for_level) / RawU256::from(100u8)
+    } else {
+        base_tax_rate
+    };
+
+    discounted_tax_rate.into()
+}
+
+/// Number of seconds before the stake of the land is fully consumed by the taxes of its
+/// `num_neighbors` neighbors, 0 if it is already the case or if no taxes are being paid.
+#[must_use]
+pub fn get_time_to_nuke(
+    land: &Land,
+    land_stake: &LandStake,
+    num_neighbors: u8,
+    current_time: u64,
+) -> U256 {
+    let tax_rate_per_neighbor = *get_tax_rate_per_neighbor(land);
+    let total_tax_rate = tax_rate_per_neighbor * RawU256::from(num_neighbors);
+
+    if total_tax_rate == RawU256::from(0u8) {
+        return U256::from(0u8);
+    }
+
+    // Number of seconds it takes for the taxes to equal the stake amount
+    let seconds_to_nuke = *land_stake.amount * RawU256::from(BASE_TIME) / total_tax_rate;
+
+    // The nuke time is the last payment time plus the seconds until nuke
+    let nuke_time = RawU256::from(land_stake.last_pay_time) + seconds_to_nuke;
+    let current_time = RawU256::from(current_time);
+
+    if nuke_time < current_time {
+        U256::from(0u8)
+    } else {
+        (nuke_time - current_time).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::Felt;
+
+    use super::*;
+    use crate::{models::Level, shared::Location};
+
+    const CENTER: Location = Location(2080);
+    const CORNER: Location = Location(0);
+
+    fn land(location: Location, sell_price: u64, level: Level) -> Land {
+        Land {
+            location,
+            block_date_bought: 0,
+            owner: Felt::ONE,
+            sell_price: U256::from(sell_price),
+            token_used: Felt::TWO,
+            level,
+        }
+    }
+
+    fn stake(last_pay_time: u64, amount: u64) -> LandStake {
+        LandStake {
+            location: CENTER,
+            last_pay_time,
+            amount: U256::from(amount),
+        }
+    }
+
+    #[test]
+    fn test_tax_rate_per_neighbor() {
+        // 1_000_000 * 2 * 5 / (8 * 100)
+        assert_eq!(
+            get_tax_rate_per_neighbor(&land(CENTER, 1_000_000, Level::Zero)),
+            U256::from(12_500u64)
+        );
+        // Only 3 neighbors in the corner
+        assert_eq!(
+            get_tax_rate_per_neighbor(&land(CORNER, 1_000_000, Level::Zero)),
+            U256::from(33_333u64)
+        );
+    }
+
+    #[test]
+    fn test_tax_rate_level_discount() {
+        assert_eq!(
+            get_tax_rate_per_neighbor(&land(CENTER, 1_000_000, Level::First)),
+            U256::from(11_250u64)
+        );
+        assert_eq!(
+            get_tax_rate_per_neighbor(&land(CENTER, 1_000_000, Level::Second)),
+            U256::from(10_625u64)
+        );
+    }
+
+    #[test]
+    fn test_taxes_per_neighbor() {
+        let land = land(CENTER, 1_000_000, Level::Zero);
+
+        assert_eq!(
+            get_taxes_per_neighbor(&land, &stake(1000, 0), 1000 + 3600),
+            U256::from(12_500u64)
+        );
+        assert_eq!(
+            get_taxes_per_neighbor(&land, &stake(1000, 0), 1000 + 1800),
+            U256::from(6_250u64)
+        );
+        // Paid in the future, nothing is due
+        assert_eq!(
+            get_taxes_per_neighbor(&land, &stake(1000, 0), 0),
+            U256::from(0u8)
+        );
+    }
+
+    #[test]
+    fn test_time_to_nuke() {
+        let land = land(CENTER, 1_000_000, Level::Zero);
+        let stake = stake(1000, 100_000);
+
+        // 4 neighbors pay 50_000 per hour, the stake lasts 2 hours after the last payment
+        assert_eq!(
+            get_time_to_nuke(&land, &stake, 4, 2000),
+            U256::from(6_200u64)
+        );
+        // Already nukable
+        assert_eq!(get_time_to_nuke(&land, &stake, 4, 9000), U256::from(0u8));
+        // Without neighbors, there are no taxes
+        assert_eq!(get_time_to_nuke(&land, &stake, 0, 2000), U256::from(0u8));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod consts;
 pub mod events;
+pub mod helpers;
 pub mod models;
 pub mod shared;
@@ -1,8 +1,15 @@
 use serde::{Deserialize, Serialize};
 use serde_aux::prelude::deserialize_number_from_string;
+use starknet::core::types::U256 as RawU256;
 use torii_ingester::{error::ToriiConversionError, get, prelude::Struct, u256::U256};
 
-use crate::shared::Location;
+use crate::{
+    consts::{
+        AUCTION_DURATION, DECIMALS_FACTOR, DROP_RATE, LINEAR_DECAY_TIME, PRICE_DECREASE_RATE,
+        RATE_DENOMINATOR, SCALING_FACTOR, TIME_SPEED,
+    },
+    shared::Location,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Model {
@@ -31,3 +38,174 @@ impl TryFrom<Struct> for Model {
         })
     }
 }
+
+impl Model {
+    /// Time elapsed since the start of the auction, in game time (accelerated by `TIME_SPEED`).
+    fn time_passed(&self, current_time: u64) -> u64 {
+        current_time.saturating_sub(self.start_time) * u64::from(TIME_SPEED)
+    }
+
+    /// Port of the contract `get_current_price`: the price decreases linearly by
+    /// `PRICE_DECREASE_RATE` percent every 2 minutes, and never goes below the floor price.
+    #[must_use]
+    pub fn get_current_price(&self, current_time: u64) -> U256 {
+        let time_passed = RawU256::from(self.time_passed(current_time));
+        let start_price = *self.start_price;
+
+        let total_decrease = start_price * RawU256::from(PRICE_DECREASE_RATE) * time_passed
+            / RawU256::from(100u32 * 120);
+
+        let decremented_price = if start_price > total_decrease {
+            start_price - total_decrease
+        } else {
+            RawU256::from(0u8)
+        };
+
+        if decremented_price <= *self.floor_price {
+            return self.floor_price;
+        }
+
+        decremented_price.into()
+    }
+
+    /// Port of the contract `get_current_price_decay_rate`, which is the price actually used
+    /// when buying an auction.
+    ///
+    /// The price first decreases linearly by `DROP_RATE` percent during `LINEAR_DECAY_TIME`,
+    /// then follows `P(t) = P0 * (1 / (1 + k*t))^2` with `k = decay_rate / SCALING_FACTOR`,
+    /// and never goes below the floor price. Once `AUCTION_DURATION` has passed, the price is 0.
+    #[must_use]
+    pub fn get_current_price_decay_rate(&self, current_time: u64) -> U256 {
+        let time_passed = self.time_passed(current_time);
+
+        // if the auction has passed a week, the price is 0
+        if time_passed >= u64::from(AUCTION_DURATION) {
+            return U256::from(0u8);
+        }
+
+        let decimals = RawU256::from(DECIMALS_FACTOR);
+        let start_price = *self.start_price;
+        let time_passed = RawU256::from(time_passed);
+
+        let current_price = if time_passed <= RawU256::from(LINEAR_DECAY_TIME) {
+            // For the first minutes we use a linear decay
+            let time_fraction = time_passed * decimals / RawU256::from(LINEAR_DECAY_TIME);
+
+            let linear_factor = decimals
+                - RawU256::from(DROP_RATE) * time_fraction / RawU256::from(RATE_DENOMINATOR);
+
+            start_price * linear_factor / decimals
+        } else {
+            let remaining_rate = RawU256::from(RATE_DENOMINATOR - DROP_RATE);
+            let price_after_linear = start_price * remaining_rate / RawU256::from(RATE_DENOMINATOR);
+
+            let progress_time = time_passed * decimals / RawU256::from(AUCTION_DURATION);
+
+            // k is the decay rate (scaled by DECIMALS_FACTOR)
+            let k = RawU256::from(self.decay_rate) * decimals / RawU256::from(SCALING_FACTOR);
+
+            // (1 + k * t), scaled by DECIMALS_FACTOR
+            let denominator = decimals + k * progress_time / decimals;
+
+            // (1 / (1 + k * t))^2, the denominator is always at least DECIMALS_FACTOR
+            let temp = decimals * decimals / denominator;
+            let decay_factor = temp * temp / decimals;
+
+            price_after_linear * decay_factor / decimals
+        };
+
+        if current_price > *self.floor_price {
+            current_price.into()
+        } else {
+            self.floor_price
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction(start_price: u64, floor_price: u64, decay_rate: u16) -> Model {
+        Model {
+            land_location: Location(1),
+            start_time: 0,
+            start_price: U256::from(start_price),
+            floor_price: U256::from(floor_price),
+            is_finished: false,
+            decay_rate,
+            sold_at_price: None,
+        }
+    }
+
+    // Same price points as the `test_price` test of the contract
+    #[test]
+    fn test_price_decay_rate() {
+        let auction = auction(1_000_000, 0, 100);
+
+        let price_points: [(u64, u64); 14] = [
+            (0, 1_000_000),
+            (2 * 60, 991_000),
+            (5 * 60, 977_500),
+            (8 * 60, 964_000),
+            (10 * 60, 955_000),
+            (60 * 60, 730_000),
+            (6 * 60 * 60, 87111),
+            (12 * 60 * 60, 76562),
+            (24 * 60 * 60, 60493),
+            (36 * 60 * 60, 49000),
+            (48 * 60 * 60, 40495),
+            (72 * 60 * 60, 28994),
+            (120 * 60 * 60, 16955),
+            (7 * 24 * 60 * 60, 0),
+        ];
+
+        for (time, price) in price_points {
+            assert_eq!(
+                auction.get_current_price_decay_rate(time / u64::from(TIME_SPEED)),
+                U256::from(price),
+                "wrong price at {time}s"
+            );
+        }
+    }
+
+    #[test]
+    fn test_price_decay_rate_floor_price() {
+        let auction = auction(1_000_000, 500_000, 100);
+
+        assert_eq!(
+            auction.get_current_price_decay_rate(60 * 60),
+            U256::from(500_000u64)
+        );
+        // Still in the linear phase, above the floor price
+        assert_eq!(
+            auction.get_current_price_decay_rate(60),
+            U256::from(977_500u64)
+        );
+    }
+
+    #[test]
+    fn test_price_before_start() {
+        let mut auction = auction(1_000_000, 0, 100);
+        auction.start_time = 1000;
+
+        assert_eq!(
+            auction.get_current_price_decay_rate(0),
+            U256::from(1_000_000u64)
+        );
+        assert_eq!(auction.get_current_price(0), U256::from(1_000_000u64));
+    }
+
+    #[test]
+    fn test_current_price() {
+        let auction = auction(1_000_000, 10_000, 100);
+
+        // 2% every 2 minutes of game time
+        assert_eq!(
+            auction.get_current_price(120 / u64::from(TIME_SPEED)),
+            U256::from(980_000u64)
+        );
+        // Clamped to the floor price
+        assert_eq!(auction.get_current_price(60 * 60), U256::from(10_000u64));
+    }
+}
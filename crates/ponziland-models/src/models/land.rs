@@ -49,6 +49,18 @@ impl FromTy for Level {
     }
 }
 
+impl Level {
+    /// Discount (in percent) applied to the taxes of a land of this level.
+    #[must_use]
+    pub fn discount(self) -> u16 {
+        match self {
+            Level::Zero => 0,
+            Level::First => 10,
+            Level::Second => 15,
+        }
+    }
+}
+
 /// Rust representation of the on-chain land model.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Land {
@@ -15,15 +15,183 @@ use torii_ingester::{
     error::ToriiConversionError,
 };
 
+use crate::consts::GRID_WIDTH;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct Location(pub u64);
 
+/// Grid geometry, ported from `coord.cairo` and `circle_expansion.cairo`.
+///
+/// Unlike the contract, nothing panics: movements outside of the grid (or from an invalid
+/// location) return `None`.
 impl Location {
-    fn coordinates(self) -> (u64, u64) {
-        (self.0 / 64, self.0 % 64)
+    /// Returns the `(row, col)` coordinates of the location on the grid.
+    #[must_use]
+    pub fn coordinates(self) -> (u64, u64) {
+        (
+            self.0 / u64::from(GRID_WIDTH),
+            self.0 % u64::from(GRID_WIDTH),
+        )
+    }
+
+    /// Returns the location at the given `(row, col)` coordinates, if it is on the grid.
+    #[must_use]
+    pub fn from_coordinates(row: u64, col: u64) -> Option<Self> {
+        let width = u64::from(GRID_WIDTH);
+        (row < width && col < width).then(|| Location::from((row, col)))
+    }
+
+    #[must_use]
+    pub fn is_valid_position(self) -> bool {
+        self.0 < u64::from(GRID_WIDTH) * u64::from(GRID_WIDTH)
+    }
+
+    /// Moves by the given offsets, returning `None` if the destination is outside of the grid.
+    fn offset(self, row_offset: i64, col_offset: i64) -> Option<Self> {
+        if !self.is_valid_position() {
+            return None;
+        }
+
+        let (row, col) = self.coordinates();
+        Self::from_coordinates(
+            row.checked_add_signed(row_offset)?,
+            col.checked_add_signed(col_offset)?,
+        )
+    }
+
+    #[must_use]
+    pub fn left(self) -> Option<Self> {
+        self.offset(0, -1)
+    }
+
+    #[must_use]
+    pub fn right(self) -> Option<Self> {
+        self.offset(0, 1)
+    }
+
+    #[must_use]
+    pub fn up(self) -> Option<Self> {
+        self.offset(-1, 0)
+    }
+
+    #[must_use]
+    pub fn down(self) -> Option<Self> {
+        self.offset(1, 0)
+    }
+
+    #[must_use]
+    pub fn up_left(self) -> Option<Self> {
+        self.offset(-1, -1)
+    }
+
+    #[must_use]
+    pub fn up_right(self) -> Option<Self> {
+        self.offset(-1, 1)
+    }
+
+    #[must_use]
+    pub fn down_left(self) -> Option<Self> {
+        self.offset(1, -1)
+    }
+
+    #[must_use]
+    pub fn down_right(self) -> Option<Self> {
+        self.offset(1, 1)
+    }
+
+    /// All the neighbors of this location that are on the grid, in the same order as the
+    /// contract: left, right, up, down, up-left, up-right, down-left, down-right.
+    #[must_use]
+    pub fn get_all_neighbors(self) -> Vec<Location> {
+        [
+            self.left(),
+            self.right(),
+            self.up(),
+            self.down(),
+            self.up_left(),
+            self.up_right(),
+            self.down_left(),
+            self.down_right(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Number of neighbors this location can have, depending on its position on the grid.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // There are at most 8 neighbors
+    pub fn max_neighbors(self) -> u8 {
+        self.get_all_neighbors().len() as u8
+    }
+
+    /// Returns `true` if both locations are adjacent, diagonals included.
+    #[must_use]
+    pub fn is_neighbor_of(self, other: Location) -> bool {
+        self.get_all_neighbors().contains(&other)
+    }
+
+    /// Number of lands in the given circle around the center of the map.
+    #[must_use]
+    pub fn lands_in_circle(circle: u64) -> u64 {
+        circle * 8
+    }
+
+    /// Number of lands in each of the 4 sections (top, right, bottom, left) of a circle.
+    #[must_use]
+    pub fn lands_per_section(circle: u64) -> u64 {
+        Self::lands_in_circle(circle) / 4
+    }
+
+    /// Location of the `index`-th land of a circle around the center of the map.
+    ///
+    /// Lands are numbered clockwise, starting from the top-left corner of the circle.
+    /// Returns `None` if the index is not part of the circle, or if the circle does not fit
+    /// in the grid.
+    #[must_use]
+    pub fn get_circle_land_position(circle: u64, index: u64) -> Option<Self> {
+        let center = u64::from(GRID_WIDTH) / 2;
+        let lands_per_section = Self::lands_per_section(circle);
+
+        if index >= Self::lands_in_circle(circle) {
+            return None;
+        }
+
+        let section = index / lands_per_section;
+        let offset = index % lands_per_section;
+
+        let (row, col) = match section {
+            // Top
+            0 => (
+                center.checked_sub(circle)?,
+                center.checked_sub(circle)? + offset,
+            ),
+            // Right
+            1 => (center.checked_sub(circle)? + offset, center + circle),
+            // Bottom
+            2 => (center + circle, (center + circle).checked_sub(offset)?),
+            // Left
+            _ => (
+                (center + circle).checked_sub(offset)?,
+                center.checked_sub(circle)?,
+            ),
+        };
+
+        Self::from_coordinates(row, col)
+    }
+
+    /// All the lands of a circle around the center of the map, in the same order as the
+    /// contract. Returns an empty list if the circle does not fit in the grid.
+    #[must_use]
+    pub fn generate_circle(circle: u64) -> Vec<Self> {
+        (0..Self::lands_in_circle(circle))
+            .map(|index| Self::get_circle_land_position(circle, index))
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default()
     }
 }
+
 impl std::fmt::Display for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (x, y) = self.coordinates();
@@ -202,4 +370,242 @@ mod test {
         let result = serde_json::from_str::<Location>(json);
         assert!(result.is_err());
     }
+
+    const WIDTH: u64 = 64;
+
+    #[test]
+    fn test_coordinates() {
+        assert_eq!(Location(0).coordinates(), (0, 0));
+        assert_eq!(Location(1).coordinates(), (0, 1));
+        assert_eq!(Location(WIDTH).coordinates(), (1, 0));
+        assert_eq!(Location(WIDTH + 1).coordinates(), (1, 1));
+
+        assert_eq!(Location::from_coordinates(0, 0), Some(Location(0)));
+        assert_eq!(Location::from_coordinates(0, 1), Some(Location(1)));
+        assert_eq!(Location::from_coordinates(1, 0), Some(Location(WIDTH)));
+        assert_eq!(Location::from_coordinates(1, 1), Some(Location(WIDTH + 1)));
+        assert_eq!(Location::from_coordinates(WIDTH, 0), None);
+        assert_eq!(Location::from_coordinates(0, WIDTH), None);
+
+        for index in 0..WIDTH * WIDTH {
+            let (row, col) = Location(index).coordinates();
+            assert_eq!(Location::from_coordinates(row, col), Some(Location(index)));
+        }
+    }
+
+    #[test]
+    fn test_move() {
+        // Test `left`
+        assert_eq!(Location(0).left(), None); // Left of top-left corner
+        assert_eq!(Location(1).left(), Some(Location(0))); // Left of (0, 1)
+        assert_eq!(Location(WIDTH).left(), None); // Left of (1, 0)
+        assert_eq!(Location(WIDTH + 1).left(), Some(Location(WIDTH))); // Left of (1, 1)
+
+        // Test `right`
+        assert_eq!(Location(0).right(), Some(Location(1))); // Right of top-left corner
+        assert_eq!(Location(1).right(), Some(Location(2))); // Right of (0, 1)
+        assert_eq!(Location(WIDTH - 1).right(), None); // Right of last column in row 0
+        assert_eq!(Location(WIDTH).right(), Some(Location(WIDTH + 1))); // Right of (1, 0)
+
+        // Test `up`
+        assert_eq!(Location(0).up(), None); // Up of top-left corner
+        assert_eq!(Location(1).up(), None); // Up of (0, 1)
+        assert_eq!(Location(WIDTH).up(), Some(Location(0))); // Up of (1, 0)
+        assert_eq!(Location(WIDTH + 1).up(), Some(Location(1))); // Up of (1, 1)
+
+        // Test `down`
+        assert_eq!(Location(0).down(), Some(Location(WIDTH))); // Down of top-left corner
+        assert_eq!(Location(1).down(), Some(Location(WIDTH + 1))); // Down of (0, 1)
+        assert_eq!(Location(WIDTH).down(), Some(Location(2 * WIDTH))); // Down of (1, 0)
+        assert_eq!(Location(WIDTH + 1).down(), Some(Location(2 * WIDTH + 1))); // Down of (1, 1)
+        assert_eq!(Location((WIDTH - 1) * WIDTH).down(), None); // Down of last row
+    }
+
+    #[test]
+    fn test_move_diagonals() {
+        let center = Location(2080); // (32, 32)
+        assert_eq!(center.up_left(), Some(Location(2080 - WIDTH - 1)));
+        assert_eq!(center.up_right(), Some(Location(2080 - WIDTH + 1)));
+        assert_eq!(center.down_left(), Some(Location(2080 + WIDTH - 1)));
+        assert_eq!(center.down_right(), Some(Location(2080 + WIDTH + 1)));
+
+        // Top-left corner
+        assert_eq!(Location(0).up_left(), None);
+        assert_eq!(Location(0).up_right(), None);
+        assert_eq!(Location(0).down_left(), None);
+        assert_eq!(Location(0).down_right(), Some(Location(WIDTH + 1)));
+
+        // Bottom-right corner
+        let corner = Location(WIDTH * WIDTH - 1);
+        assert_eq!(corner.up_left(), Some(Location(WIDTH * WIDTH - WIDTH - 2)));
+        assert_eq!(corner.up_right(), None);
+        assert_eq!(corner.down_left(), None);
+        assert_eq!(corner.down_right(), None);
+
+        // Does not wrap around the rows
+        assert_eq!(Location(WIDTH).up_left(), None);
+        assert_eq!(Location(WIDTH - 1).down_right(), None);
+    }
+
+    #[test]
+    fn test_is_valid_position() {
+        assert!(Location(10).is_valid_position());
+        assert!(Location(4095).is_valid_position());
+        assert!(!Location(4096).is_valid_position());
+        assert!(!Location(10000).is_valid_position());
+
+        // Nothing can move out of an invalid position
+        assert_eq!(Location(4096).left(), None);
+        assert_eq!(Location(4096).up(), None);
+        assert!(Location(10000).get_all_neighbors().is_empty());
+    }
+
+    #[test]
+    fn test_max_neighbors() {
+        // Corners
+        assert_eq!(Location(0).max_neighbors(), 3);
+        assert_eq!(Location(63).max_neighbors(), 3);
+        assert_eq!(Location(63 * 64).max_neighbors(), 3);
+        assert_eq!(Location(64 * 64 - 1).max_neighbors(), 3);
+        // Borders
+        assert_eq!(Location(1).max_neighbors(), 5);
+        assert_eq!(Location(64).max_neighbors(), 5);
+        // Center
+        assert_eq!(Location(2080).max_neighbors(), 8);
+    }
+
+    #[test]
+    fn test_get_all_neighbors() {
+        assert_eq!(
+            Location(65).get_all_neighbors(),
+            vec![
+                Location(64),
+                Location(66),
+                Location(1),
+                Location(129),
+                Location(0),
+                Location(2),
+                Location(128),
+                Location(130),
+            ]
+        );
+        assert_eq!(
+            Location(0).get_all_neighbors(),
+            vec![Location(1), Location(64), Location(65)]
+        );
+    }
+
+    #[test]
+    fn test_neighbors_whole_grid() {
+        for index in 0..WIDTH * WIDTH {
+            let location = Location(index);
+            let (row, col) = location.coordinates();
+            let neighbors = location.get_all_neighbors();
+
+            let on_row_border = row == 0 || row == WIDTH - 1;
+            let on_col_border = col == 0 || col == WIDTH - 1;
+            let expected = match (on_row_border, on_col_border) {
+                (true, true) => 3,
+                (true, false) | (false, true) => 5,
+                (false, false) => 8,
+            };
+            assert_eq!(neighbors.len(), expected, "wrong neighbors for {location}");
+            assert_eq!(usize::from(location.max_neighbors()), expected);
+
+            for neighbor in neighbors {
+                assert!(neighbor.is_valid_position());
+                assert_ne!(neighbor, location);
+                assert!(
+                    neighbor.is_neighbor_of(location),
+                    "{neighbor} <-> {location}"
+                );
+
+                let (neighbor_row, neighbor_col) = neighbor.coordinates();
+                assert!(row.abs_diff(neighbor_row) <= 1);
+                assert!(col.abs_diff(neighbor_col) <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_circle_sizes() {
+        assert_eq!(Location::lands_in_circle(0), 0);
+        assert_eq!(Location::lands_in_circle(1), 8);
+        assert_eq!(Location::lands_in_circle(3), 24);
+        assert_eq!(Location::lands_per_section(1), 2);
+        assert_eq!(Location::lands_per_section(3), 6);
+    }
+
+    #[test]
+    fn test_first_circle() {
+        // The first circle surrounds the center of the map, clockwise from the top-left
+        let circle = Location::generate_circle(1);
+        assert_eq!(
+            circle,
+            vec![
+                Location(31 * WIDTH + 31),
+                Location(31 * WIDTH + 32),
+                Location(31 * WIDTH + 33),
+                Location(32 * WIDTH + 33),
+                Location(33 * WIDTH + 33),
+                Location(33 * WIDTH + 32),
+                Location(33 * WIDTH + 31),
+                Location(32 * WIDTH + 31),
+            ]
+        );
+
+        let mut neighbors = Location(2080).get_all_neighbors();
+        let mut sorted_circle = circle.clone();
+        neighbors.sort_by_key(|location| location.0);
+        sorted_circle.sort_by_key(|location| location.0);
+        assert_eq!(neighbors, sorted_circle);
+    }
+
+    #[test]
+    fn test_circle_land_position() {
+        assert_eq!(
+            Location::get_circle_land_position(2, 0),
+            Some(Location(30 * WIDTH + 30))
+        );
+        // First land of each section
+        assert_eq!(
+            Location::get_circle_land_position(2, 4),
+            Some(Location(30 * WIDTH + 34))
+        );
+        assert_eq!(
+            Location::get_circle_land_position(2, 8),
+            Some(Location(34 * WIDTH + 34))
+        );
+        assert_eq!(
+            Location::get_circle_land_position(2, 12),
+            Some(Location(34 * WIDTH + 30))
+        );
+        // Out of the circle
+        assert_eq!(Location::get_circle_land_position(2, 16), None);
+        assert_eq!(Location::get_circle_land_position(0, 0), None);
+        // The top of the 32nd circle is on the grid, but not its right side
+        assert_eq!(Location::get_circle_land_position(32, 4), Some(Location(4)));
+        assert_eq!(Location::get_circle_land_position(32, 64), None);
+        assert_eq!(Location::get_circle_land_position(33, 0), None);
+    }
+
+    #[test]
+    fn test_all_circles() {
+        let mut seen = std::collections::HashSet::from([2080]);
+
+        for circle in 1..32 {
+            let lands = Location::generate_circle(circle);
+            assert_eq!(lands.len() as u64, Location::lands_in_circle(circle));
+
+            for land in lands {
+                let (row, col) = land.coordinates();
+                assert_eq!(row.abs_diff(32).max(col.abs_diff(32)), circle);
+                assert!(seen.insert(land.0), "{land} is in two circles");
+            }
+        }
+
+        // Circles are covering the whole grid, except the first row and column
+        assert_eq!(seen.len() as u64, 63 * 63);
+        assert!(Location::generate_circle(32).is_empty());
+    }
 }
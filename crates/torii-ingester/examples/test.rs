@@ -22,6 +22,9 @@ pub async fn main() {
     let mut event_stream = client.get_all_events().expect("Failed to fetch events");
 
     while let Some(event) = event_stream.next().await {
-        println!("{event:?}");
+        match event {
+            Ok(event) => println!("{event:?}"),
+            Err(err) => eprintln!("{err}"),
+        }
     }
 }
@@ -6,14 +6,17 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use starknet::core::types::Felt;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::Stream;
 use torii_client::Client as GrpcClient;
+use tracing::warn;
 
-// TODO(Red): Make sure we loose no messages between the catchup and the listen
-// (Maybe add the listen at the same time we do the catchup, and if we keep the event IDs somewhere, we can work with this system)
+// NOTE: gRPC updates have no event id, so they cannot be used to resume a sync.
+// To lose no message between the catchup and the listen, subscribe first, then catch up with SQL
+// from a persisted event id, and use the subscription only as a signal to catch up again.
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -23,8 +26,30 @@ pub enum Error {
     GrpcSubscriptionError(torii_client::error::Error),
     #[error("SQL Query error: {0}")]
     SqlError(#[from] super::torii_sql::Error),
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
+    /// A row of torii could not be read. The rest of the stream is still valid.
+    #[error("Invalid row {event_id}: {reason}")]
+    InvalidRow {
+        name: String,
+        event_id: String,
+        data: Value,
+        reason: String,
+    },
 }
 
+/// Maximum number of attempts for a SQL request failing with a transient error.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry of a SQL request, doubled on each new attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Number of rows read from torii per SQL request.
+const PAGE_SIZE: usize = 100;
+
+/// Number of digits of the block number in a torii event id.
+const BLOCK_DIGITS: usize = 62;
+
 pub struct ToriiConfiguration {
     pub base_url: String,
     pub world_address: Felt,
@@ -57,6 +82,15 @@ impl RawToriiData {
             RawToriiData::Grpc(structure) => &structure.name,
         }
     }
+
+    /// Raw torii event id (`block:tx_hash:event_idx`) of the data, only known for SQL results.
+    #[must_use]
+    pub fn event_id(&self) -> Option<&str> {
+        match self {
+            RawToriiData::Json { event_id, .. } => Some(event_id),
+            RawToriiData::Grpc(_) => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -65,9 +99,31 @@ struct QueryResponse {
     #[serde(deserialize_with = "deserialize_nested_json")]
     data: Value,
     event_id: String,
+    row_id: i64,
     created_at: String,
 }
 
+/// Last row of a page of a SQL request, the next page starts right after it.
+///
+/// Rows are ordered by event id, then by rowid between rows of the same event. Pages start after
+/// the previous one instead of skipping an offset, so that each one is read from an index on the
+/// event id rather than by sorting the whole table again.
+struct PageEnd {
+    event_id: String,
+    row_id: i64,
+}
+
+impl PageEnd {
+    /// SQL condition matching the rows after this one, in the given table.
+    fn condition(&self, table: &str) -> String {
+        format!(
+            "({table}.event_id, {table}.rowid) > ('{}', {})",
+            self.event_id.replace('\'', "''"),
+            self.row_id
+        )
+    }
+}
+
 impl ToriiClient {
     /// Create a new instance of `ToriiClient`.
     ///
@@ -94,15 +150,27 @@ impl ToriiClient {
     pub fn get_all_events_after(
         &self,
         instant: chrono::DateTime<Utc>,
-    ) -> Result<impl Stream<Item = RawToriiData>, Error> {
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
         self.do_events_sql_request(format!("em.created_at > \"{}\"", instant.format("%F %T")))
     }
 
+    /// Get all events after the given torii event id, ordered by event id.
+    ///
+    /// # Errors
+    /// Returns an error if the cursor is not a valid torii event id, or if the SQL query fails.
+    pub fn get_all_events_from_cursor(
+        &self,
+        event_id: &str,
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
+        let cursor = torii_cursor(event_id)?;
+        self.do_events_sql_request(format!("em.event_id > '{cursor}'"))
+    }
+
     /// Get all events.
     ///
     /// # Errors
     /// Returns an error if the SQL query fails.
-    pub fn get_all_events(&self) -> Result<impl Stream<Item = RawToriiData>, Error> {
+    pub fn get_all_events(&self) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
         self.do_events_sql_request("1=1")
     }
 
@@ -110,10 +178,24 @@ impl ToriiClient {
     ///
     /// # Errors
     /// Returns an error if the SQL query fails.
-    pub fn get_all_entities(&self) -> Result<impl Stream<Item = RawToriiData>, Error> {
+    pub fn get_all_entities(
+        &self,
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
         self.do_entities_sql_request("1=1")
     }
 
+    /// Get all entities after the given torii event id, ordered by event id.
+    ///
+    /// # Errors
+    /// Returns an error if the cursor is not a valid torii event id, or if the SQL query fails.
+    pub fn get_all_entities_from_cursor(
+        &self,
+        event_id: &str,
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
+        let cursor = torii_cursor(event_id)?;
+        self.do_entities_sql_request(format!("e.event_id > '{cursor}'"))
+    }
+
     /// Get all entities after a given instant.
     ///
     /// # Errors
@@ -121,7 +203,7 @@ impl ToriiClient {
     pub fn get_all_entities_after(
         &self,
         instant: chrono::DateTime<Utc>,
-    ) -> Result<impl Stream<Item = RawToriiData>, Error> {
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
         self.do_entities_sql_request(format!("e.created_at > \"{}\"", instant.format("%F %T")))
     }
 
@@ -186,15 +268,17 @@ impl ToriiClient {
     fn do_entities_sql_request(
         &self,
         r#where: impl Into<String>,
-    ) -> Result<impl Stream<Item = RawToriiData>, Error> {
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
         let r#where = r#where.into();
-        self.do_request(move |current_offset| {
+        self.do_request(move |after| {
+            let after = after.map_or_else(|| "1=1".to_string(), |after| after.condition("e"));
             format!(r"
-                SELECT concat( m.namespace, '-', m.name) as selector, e.data as data, e.event_id as event_id, e.created_at as created_at
+                SELECT concat( m.namespace, '-', m.name) as selector, e.data as data, e.event_id as event_id, e.rowid as row_id, e.created_at as created_at
                 FROM entities_historical e
                 LEFT JOIN models m on e.model_id = m.id
-                WHERE {where}
-                LIMIT 100 OFFSET {current_offset};
+                WHERE ({where}) AND {after}
+                ORDER BY e.event_id, e.rowid
+                LIMIT {PAGE_SIZE};
                 ")
         })
     }
@@ -202,63 +286,80 @@ impl ToriiClient {
     fn do_events_sql_request(
         &self,
         r#where: impl Into<String>,
-    ) -> Result<impl Stream<Item = RawToriiData>, Error> {
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error> {
         let r#where = r#where.into();
-        self.do_request(move |current_offset| {
+        self.do_request(move |after| {
+            let after = after.map_or_else(|| "1=1".to_string(), |after| after.condition("em"));
             format!(r"
-                SELECT concat(m.namespace, '-',  m.name) as selector, em.data as data, em.event_id as event_id, em.created_at as created_at
+                SELECT concat(m.namespace, '-',  m.name) as selector, em.data as data, em.event_id as event_id, em.rowid as row_id, em.created_at as created_at
                 FROM event_messages_historical em
                 LEFT JOIN models m on em.model_id = m.id
-                WHERE {where}
-                LIMIT 100 OFFSET {current_offset};
+                WHERE ({where}) AND {after}
+                ORDER BY em.event_id, em.rowid
+                LIMIT {PAGE_SIZE};
                 ")
         })
     }
 
     #[allow(clippy::unnecessary_wraps)] // This actually makes sense
-    fn do_request<F, T>(&self, request: F) -> Result<impl Stream<Item = RawToriiData>, Error>
+    fn do_request<F, T>(
+        &self,
+        request: F,
+    ) -> Result<impl Stream<Item = Result<RawToriiData, Error>>, Error>
     where
         T: Into<String>,
         // We need a function that:
         // - can be send between threads (for the tokio::spawn)
         // - that lives for the entire duration of the program (easy if no internal state is used)
-        // - Takes a u64 as a parameter, and returns something that can be .into() to a String (for move sementics purposes)
-        F: 'static + Send + Fn(u64) -> T,
+        // - Takes the end of the previous page, and returns something that can be .into() to a String (for move sementics purposes)
+        F: 'static + Send + Fn(Option<&PageEnd>) -> T,
     {
         let sql_client = self.sql_client.clone();
 
-        let (tx, rx) = mpsc::channel::<RawToriiData>(32);
+        let (tx, rx) = mpsc::channel::<Result<RawToriiData, Error>>(32);
 
         tokio::spawn(async move {
-            let mut current_offset = 0;
+            let mut after = None;
 
             loop {
-                // TODO(red): Add base offset support
-                let request: Vec<QueryResponse> = sql_client
-                    .query(request(current_offset).into())
-                    .await
-                    // TODO: Remove usage of panics
-                    .expect("ohno");
-
-                if request.is_empty() {
-                    break;
-                }
+                let request =
+                    match query_with_retry(&sql_client, request(after.as_ref()).into()).await {
+                        Ok(request) => request,
+                        Err(err) => {
+                            // The stream ends on the error, the caller can retry later on
+                            let _ = tx.send(Err(err)).await;
+                            return;
+                        }
+                    };
 
-                current_offset += 100;
+                let Some(last) = request.last() else {
+                    break;
+                };
+                after = Some(PageEnd {
+                    event_id: last.event_id.clone(),
+                    row_id: last.row_id,
+                });
 
                 // We can send data through the wire.
                 for elem in request {
-                    let event = RawToriiData::Json {
-                        name: elem.selector,
-                        data: elem.data,
-                        event_id: elem.event_id,
-                        // TODO: Migrate this to something else than panics
-                        at: NaiveDateTime::parse_from_str(&elem.created_at, "%F %T")
-                            .unwrap()
-                            .and_utc(),
+                    let event = match NaiveDateTime::parse_from_str(&elem.created_at, "%F %T") {
+                        Ok(at) => Ok(RawToriiData::Json {
+                            name: elem.selector,
+                            data: elem.data,
+                            event_id: elem.event_id,
+                            at: at.and_utc(),
+                        }),
+                        Err(err) => Err(Error::InvalidRow {
+                            name: elem.selector,
+                            event_id: elem.event_id,
+                            data: elem.data,
+                            reason: format!("invalid created_at {}: {err}", elem.created_at),
+                        }),
                     };
-                    // TODO: Migrate this to something else than panics
-                    tx.send(event).await.expect("Error");
+                    if tx.send(event).await.is_err() {
+                        // The stream was dropped, no need to continue
+                        return;
+                    }
                 }
             }
         });
@@ -267,6 +368,65 @@ impl ToriiClient {
     }
 }
 
+/// Runs a SQL query on torii, retrying transient errors with an exponential backoff.
+async fn query_with_retry(
+    sql_client: &SqlClient,
+    query: String,
+) -> Result<Vec<QueryResponse>, Error> {
+    let mut attempt = 1;
+    loop {
+        match sql_client.query(query.clone()).await {
+            Err(err) if err.is_transient() && attempt < MAX_ATTEMPTS => {
+                let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                warn!(
+                    "Transient error on torii SQL request (attempt {attempt}/{MAX_ATTEMPTS}), retrying in {delay:?}: {err}"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result.map_err(Error::from),
+        }
+    }
+}
+
+/// Formats a torii event id (`block:tx_hash:event_idx`) exactly like torii does, so that it
+/// compares to the ids of torii as a string.
+///
+/// Torii pads the block to a fixed width, so ids are ordered by block. Within a block, they are
+/// ordered by transaction hash, then by event index up to 255 events per transaction.
+///
+/// As the parts of the cursor are checked to be hex numbers, the result is safe to put in a query.
+fn torii_cursor(event_id: &str) -> Result<String, Error> {
+    let invalid = || Error::InvalidCursor(event_id.to_string());
+
+    let parts = event_id
+        .split(':')
+        .map(|part| {
+            part.strip_prefix("0x")
+                .filter(|digits| {
+                    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit())
+                })
+                .map(|digits| {
+                    digits
+                        .to_ascii_lowercase()
+                        .trim_start_matches('0')
+                        .to_string()
+                })
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let [block, tx_hash, event_idx] = <[String; 3]>::try_from(parts).map_err(|_| invalid())?;
+    if block.len() > BLOCK_DIGITS {
+        return Err(invalid());
+    }
+
+    // Zero is the only number without digits once stripped
+    let tx_hash = if tx_hash.is_empty() { "0" } else { &tx_hash };
+    Ok(format!(
+        "0x{block:0>BLOCK_DIGITS$}:0x{tx_hash}:0x{event_idx:0>2}"
+    ))
+}
+
 fn deserialize_nested_json<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
     T: DeserializeOwned,
@@ -275,3 +435,48 @@ where
     let json_string: String = String::deserialize(deserializer)?.replace("\\\"", "\"");
     serde_json::from_str::<T>(&json_string).map_err(serde::de::Error::custom)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_torii_cursor() {
+        let block = "000000000000000000000000000000000000000000000000000000000b63a9";
+        let id = format!(
+            "0x{block}:0x5f26258a75882780784979d970a3579c091e92073d61f7e90260e1133f75c8a:0x10"
+        );
+        assert_eq!(torii_cursor(&id).unwrap(), id);
+        // Padded to other widths, the id is formatted like torii
+        assert_eq!(
+            torii_cursor("0xB63A9:0x0001234:0x1").unwrap(),
+            format!("0x{block}:0x1234:0x01")
+        );
+        assert_eq!(
+            torii_cursor("0x0:0x00:0x00").unwrap(),
+            format!("0x{}:0x0:0x00", "0".repeat(62))
+        );
+
+        assert!(torii_cursor("").is_err());
+        assert!(torii_cursor("0x:0x1234:0x01").is_err());
+        assert!(torii_cursor("0x1:0x1234").is_err());
+        assert!(torii_cursor("0x1:0x1234:0x1:0x1").is_err());
+        assert!(torii_cursor(&format!("0x1{}:0x1:0x1", "0".repeat(62))).is_err());
+        assert!(torii_cursor("0x12' OR 1=1 --:0x1:0x1").is_err());
+    }
+
+    #[test]
+    fn test_event_id_order() {
+        // Ids of torii are ordered by block, then event index as strings
+        let ids = [
+            "0xff:0xfff:0xff",
+            "0x100:0x10:0x01",
+            "0x100:0x10:0x02",
+            "0x100:0x10:0xff",
+            "0x1000:0x2:0x01",
+        ]
+        .map(|id| torii_cursor(id).unwrap());
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
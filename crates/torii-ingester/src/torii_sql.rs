@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use reqwest::{Client, ClientBuilder, IntoUrl, Url};
+use reqwest::{Client, ClientBuilder, IntoUrl, StatusCode, Url};
 use serde::de::DeserializeOwned;
 use std::fmt::Debug;
 use thiserror::Error;
@@ -18,6 +18,19 @@ pub enum Error {
     ResponseError(reqwest::Error),
     #[error("Server returned invalid response: {0:?}")]
     BadResponse(Option<String>),
+    #[error("Server is unavailable ({0}): {1:?}")]
+    Unavailable(StatusCode, Option<String>),
+}
+
+impl Error {
+    /// Whether the error is transient, and the request can be retried as is.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Error::RequestError(_) | Error::ResponseError(_) | Error::Unavailable(..)
+        )
+    }
 }
 
 #[derive(Clone)]
@@ -85,10 +98,13 @@ impl SqlClient {
             .await
             .map_err(Error::RequestError)?;
 
-        if response.status() == 200 {
+        let status = response.status();
+        if status == 200 {
             // Return the parsed response
             let body = response.text().await.map_err(Error::ResponseError)?;
             serde_json::from_str(&body).map_err(|_| Error::BadResponse(Some(body)))
+        } else if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            Err(Error::Unavailable(status, response.text().await.ok()))
         } else {
             Err(Error::BadResponse(response.text().await.ok()))
         }
@@ -44,6 +44,18 @@ impl U256 {
         (self.0.low(), self.0.high())
     }
 
+    /// Adds two numbers, returning `None` on overflow.
+    #[must_use]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let (low, carry) = self.0.low().overflowing_add(rhs.0.low());
+        let high = self
+            .0
+            .high()
+            .checked_add(rhs.0.high())?
+            .checked_add(u128::from(carry))?;
+        Some(Self::from_words(low, high))
+    }
+
     #[must_use]
     pub fn from_u64_words(value: [u64; 4]) -> Self {
         // Convert 4 u64 words into 2 u128 words (low, high)
@@ -232,6 +244,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(
+            U256::from(2u8).checked_add(U256::from(3u8)),
+            Some(U256::from(5u8))
+        );
+        // The carry of the low word goes to the high one
+        assert_eq!(
+            U256::from_words(u128::MAX, 0).checked_add(U256::from(1u8)),
+            Some(U256::from_words(0, 1))
+        );
+        assert_eq!(
+            U256::from_words(u128::MAX, u128::MAX).checked_add(U256::from(1u8)),
+            None
+        );
+        assert_eq!(
+            U256::from_words(0, u128::MAX).checked_add(U256::from_words(0, 1)),
+            None
+        );
+    }
+
     #[test]
     fn test_from_u64_words() {
         // Test zero values